@@ -1,9 +1,33 @@
-use crate::algorithm::odd_path::shortest_odd_path;
+use crate::algorithm::k_best_diversion::odd_path_alternatives;
+use crate::algorithm::parity_path::{shortest_path_with_parity, Parity};
 use crate::algorithm::utility::split_edges;
 use crate::structure::graph::edge::Edge;
 use crate::structure::graph::undirected_graph::UndirectedGraph;
 use crate::structure::path_result::{PathResult, PathResult::*};
 use crate::structure::weight::Weight;
+use crate::utility::misc::repeat;
+use std::fmt::{Display, Formatter};
+
+/// The directed bottleneck path tries at most this many bottleneck paths before it gives up.
+pub const PATH_LIMIT: usize = 1000;
+
+#[derive(Debug, PartialEq)]
+pub enum BottleneckError {
+    /// This many bottleneck paths all passed the bottleneck the other way, so the search gave up.
+    GaveUp(usize),
+}
+
+impl Display for BottleneckError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BottleneckError::GaveUp(tried) => write!(
+                f,
+                "The first {} bottleneck paths all pass the bottleneck the other way, so the search gave up",
+                tried
+            ),
+        }
+    }
+}
 
 /**
 Problem: Shortest Bottleneck Path
@@ -17,17 +41,210 @@ pub fn shortest_bottleneck_path<W: Weight, E: Edge<W>>(
     t: usize,
     (bottle_from, bottle_to): (usize, usize),
 ) -> PathResult<W, E> {
-    let bottleneck = graph[&bottle_from]
+    let bottleneck: Vec<E> = graph[&bottle_from]
         .iter()
         .filter(|e| e.to() == bottle_to)
-        .map(|e| e.clone())
+        .cloned()
         .collect();
-    let (split, map) = split_edges(&graph, bottleneck);
-    match shortest_odd_path(&split, s, t) {
+    shortest_path_with_parity(graph, s, t, &bottleneck, Parity::Odd)
+}
+
+/**
+Problem: Shortest Bottleneck Path through a vertex
+In: an undirected graph G, two vertices s,t in V(G), and a 'bottleneck' vertex x in V(G)
+Out: the shortest simple s-t-path in G that passes through x, if one exists
+
+We split x in two: a copy x' gets a copy of every edge at x, and x and x' are joined by an edge of weight 0.
+A simple s-t-path through that edge enters x and leaves x' (or the other way around) through edges of two
+different neighbours, so it is a simple s-t-path through x in G, and the other way around.
+The copies are mapped back to the edges of x, and the edge of weight 0 is left out.
+*/
+pub fn shortest_vertex_bottleneck_path<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+    x: usize,
+) -> PathResult<W, E> {
+    let copy = graph.n();
+    let mut doubled = UndirectedGraph::new(copy + 1);
+    let mut copies = Vec::new();
+    let mut zero = None;
+    for e in graph.edges() {
+        if e.from() < e.to() {
+            doubled.add_edge(e.clone());
+        }
+        if e.to() == x && e.from() != x {
+            // The edge (y, x) becomes (y, x'), and the rest of it is an edge (x', x) of weight 0.
+            let (to_copy, to_x) = e.subdivide(copy);
+            doubled.add_edge(to_copy.clone());
+            copies.push((to_copy, e));
+            zero.get_or_insert(to_x);
+        }
+    }
+    match zero {
+        Some(zero) => doubled.add_edge(zero),
+        None => return Impossible,
+    }
+
+    let original = |e: &E| {
+        copies.iter().find_map(|(c, original)| {
+            if c == e {
+                Some(original.clone())
+            } else if c.reverse() == *e {
+                Some(original.reverse())
+            } else {
+                None
+            }
+        })
+    };
+    match shortest_bottleneck_path(&doubled, s, t, (x, copy)) {
         Impossible => Impossible,
         Possible { cost, path } => Possible {
             cost,
-            path: path.iter().flat_map(|e| map(e)).collect(),
+            path: path
+                .iter()
+                .filter(|e| !(e.from() == copy && e.to() == x || e.from() == x && e.to() == copy))
+                .map(|e| original(e).unwrap_or_else(|| e.clone()))
+                .collect(),
         },
     }
 }
+
+/**
+Problem: Shortest Directed Bottleneck Path
+In: an undirected graph G, two vertices s,t in V(G), and a 'bottleneck' edge (u,v) in E(G)
+Out: the shortest s-t-path in G that passes through (u,v) from u to v, if one exists,
+     or an error when none of the first `PATH_LIMIT` bottleneck paths does
+
+The bottleneck paths are the odd s-t-paths in the graph where every other edge is split, so we enumerate them in order
+of cost with `odd_path_alternatives`, and take the first one that goes from u to v. A path that has passed (v,u) can't
+pass it again, so Yen's spurs after (v,u) are pruned. There is no such path at all when s doesn't reach u without v,
+or v doesn't reach t without u, but when every path passes (u,v) the other way, there can be exponentially many of
+them, so we give up after `PATH_LIMIT` paths.
+*/
+pub fn shortest_directed_bottleneck_path<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+    (u, v): (usize, usize),
+) -> Result<PathResult<W, E>, BottleneckError> {
+    let reaches = |from: usize, to: usize, avoid: usize| {
+        let mut seen = repeat(graph.n(), false);
+        let mut stack = vec![from];
+        seen[from] = true;
+        while let Some(x) = stack.pop() {
+            for e in graph.N(x) {
+                if !seen[e.to()] && e.to() != avoid {
+                    seen[e.to()] = true;
+                    stack.push(e.to());
+                }
+            }
+        }
+        seen[to]
+    };
+    if s == v || t == u || !reaches(s, u, v) || !reaches(v, t, u) {
+        return Ok(Impossible);
+    }
+
+    let bottleneck = graph[&u].iter().filter(|e| e.to() == v).cloned().collect();
+    let (split, map) = split_edges(graph, bottleneck);
+    let alternatives =
+        odd_path_alternatives(split, map, s, t).pruned(Box::new(move |root: &[E]| {
+            !root.iter().any(|e| e.from() == v && e.to() == u)
+        }));
+    for (tried, (cost, path)) in alternatives.enumerate() {
+        if tried == PATH_LIMIT {
+            return Err(BottleneckError::GaveUp(PATH_LIMIT));
+        }
+        if path.iter().any(|e| e.from() == u && e.to() == v) {
+            return Ok(Possible { cost, path });
+        }
+    }
+    Ok(Impossible)
+}
+
+#[cfg(test)]
+mod test_bottleneck_path {
+    use crate::algorithm::bottleneck_path::{
+        shortest_directed_bottleneck_path, shortest_vertex_bottleneck_path, BottleneckError,
+        PATH_LIMIT,
+    };
+    use crate::algorithm::test_utility::{brute_force, random_graph};
+    use crate::structure::graph::edge::{BasicEdge, Edge};
+    use crate::structure::graph::undirected_graph::UndirectedGraph;
+    use crate::structure::path_result::PathResult::*;
+    use crate::structure::weight::Weighted;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_vertex_matches_brute_force() {
+        let mut random = Random::new(45);
+        for _ in 0..50 {
+            let graph = random_graph(&mut random);
+            let (s, t, x) = (0, 6, random.below(7));
+            let through_x = |path: &[BasicEdge<u64>]| x == s || path.iter().any(|e| e.to() == x);
+            match shortest_vertex_bottleneck_path(&graph, s, t, x) {
+                Possible { cost, path } => {
+                    assert_eq!(Some(cost), brute_force(&graph, s, t, &through_x));
+                    assert_eq!(path.iter().map(|e| e.weight()).sum::<u64>(), cost);
+                    assert_eq!((path[0].from(), path.last().unwrap().to()), (s, t));
+                    assert!(path.windows(2).all(|w| w[0].to() == w[1].from()));
+                    assert!(path.iter().any(|e| e.to() == x) || x == s);
+                }
+                Impossible => assert_eq!(brute_force(&graph, s, t, &through_x), None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_directed_matches_brute_force() {
+        let mut random = Random::new(46);
+        for _ in 0..50 {
+            let graph = random_graph(&mut random);
+            let edges = graph.edges();
+            if edges.is_empty() {
+                continue;
+            }
+            let b = &edges[random.below(edges.len())];
+            let (u, v) = (b.from(), b.to());
+            let from_u_to_v =
+                |path: &[BasicEdge<u64>]| path.iter().any(|e| (e.from(), e.to()) == (u, v));
+            match shortest_directed_bottleneck_path(&graph, 0, 6, (u, v)).unwrap() {
+                Possible { cost, path } => {
+                    assert_eq!(Some(cost), brute_force(&graph, 0, 6, &from_u_to_v));
+                    assert!(from_u_to_v(&path));
+                }
+                Impossible => assert_eq!(brute_force(&graph, 0, 6, &from_u_to_v), None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_directed_gives_up_on_many_reverse_paths() {
+        // s = 0 reaches the corner w = 24 of a 5x5 grid in 8512 ways, more than `PATH_LIMIT`, and every bottleneck path goes on
+        // w, v, u, t: the only way from u to t without w.
+        let (w, u, v, t) = (24, 25, 26, 27);
+        let mut graph = UndirectedGraph::<u64, BasicEdge<u64>>::new(28);
+        for x in 0..5 {
+            for y in 0..5 {
+                if x + 1 < 5 {
+                    graph.add_edge(BasicEdge::new(5 * y + x, 5 * y + x + 1, 1));
+                }
+                if y + 1 < 5 {
+                    graph.add_edge(BasicEdge::new(5 * y + x, 5 * y + x + 5, 1));
+                }
+            }
+        }
+        for (a, b) in [(w, u), (w, v), (u, v), (u, t), (w, t)] {
+            graph.add_edge(BasicEdge::new(a, b, 1));
+        }
+        assert!(matches!(
+            shortest_directed_bottleneck_path(&graph, 0, t, (u, v)),
+            Err(BottleneckError::GaveUp(PATH_LIMIT))
+        ));
+        assert!(matches!(
+            shortest_directed_bottleneck_path(&graph, 0, t, (v, u)),
+            Ok(Possible { cost: 11, .. })
+        ));
+    }
+}
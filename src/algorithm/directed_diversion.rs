@@ -0,0 +1,437 @@
+use crate::structure::cost::{Cost, Cost::*};
+use crate::structure::graph::directed_graph::DirectedGraph;
+use crate::structure::graph::directed_planar_graph::DirectedPlanarGraph;
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::weight::{Weight, Weighted};
+use crate::utility::misc::{debug, repeat};
+use queues::{IsQueue, Queue};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{Display, Formatter};
+
+/// The exact fallback is exponential, so it gives up after this many branches.
+pub const BRANCH_LIMIT: usize = 100_000;
+
+#[derive(Debug, PartialEq)]
+pub enum DirectedDiversionError {
+    /// No directed s-t-path uses b, or no set of arcs leaves only paths through b.
+    NoDiversion,
+    /// The exact search gave up after this many branches, before it could prove an answer.
+    GaveUp(usize),
+}
+
+impl Display for DirectedDiversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DirectedDiversionError::NoDiversion => write!(f, "No cut found"),
+            DirectedDiversionError::GaveUp(limit) => write!(
+                f,
+                "The exact search gave up after {} branches, before it found the cheapest diversion",
+                limit
+            ),
+        }
+    }
+}
+
+/// A diversion set with its cost.
+type Diversion<W> = (W, Vec<PlanarEdge<W>>);
+
+/**
+Problem: Directed Planar Network Diversion
+In: a directed planar graph G, two vertices s,t in V(G), and a diversion arc b = (du, dv) in A(G)
+Out: a minimum-cost set of arcs X such that every directed s-t-path in G - X uses b,
+     while some directed s-t-path in G - X still uses b.
+
+The vertices that s reaches without b after the cut form a side S with s, du in S and dv, t not in S,
+and all arcs leaving S except b are cut. So the cut is a closed curve in the dual through b*,
+with S on its left, that winds once around s but not around t.
+We count the signed crossings with a directed s-t-path P that avoids b, and run Dijkstra on pairs (face, winding),
+from (left(b), 0) to (right(b), 1).
+
+The curve is only a lower bound, since it does not know that s has to reach du inside of S,
+and that dv has to reach t outside of S. When the cut from the curve is not a diversion,
+or when the embedding is not a connected plane embedding, we fall back to `exact_directed_diversion`, which gives up
+after `BRANCH_LIMIT` branches.
+*/
+pub fn directed_network_diversion<W: Weight>(
+    graph: &DirectedPlanarGraph<W>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+) -> Result<Diversion<W>, DirectedDiversionError> {
+    match winding_diversion(graph, s, t, (du, dv)) {
+        Curve::Diversion(found) => Ok(found),
+        Curve::Impossible => Err(DirectedDiversionError::NoDiversion),
+        Curve::Bound(bound) => exact_directed_diversion(graph.real(), s, t, (du, dv), bound),
+    }
+}
+
+/// What the winding curve settles on its own.
+enum Curve<W: Weight> {
+    /// The cut of the curve is an optimal diversion.
+    Diversion(Diversion<W>),
+    /// No diversion exists.
+    Impossible,
+    /// The exact search has to decide, and every diversion costs at least this much.
+    Bound(W),
+}
+
+fn winding_diversion<W: Weight>(
+    graph: &DirectedPlanarGraph<W>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+) -> Curve<W> {
+    let real = graph.real();
+    let b = real.arc(du, dv);
+
+    let path = match directed_bfs(real, s, t, (du, dv)) {
+        Some(path) => path,
+        None => {
+            debug(format!(
+                "Could not find any directed {}-{}-path that doesn't use the diversion arc, no diversion is needed.",
+                s, t
+            ));
+            return if through_diversion(real, s, t, &b) {
+                Curve::Diversion((0.into(), Vec::new()))
+            } else {
+                Curve::Impossible
+            };
+        }
+    };
+
+    let planar = graph.planar();
+    // Euler's formula only holds for a connected plane embedding.
+    if !planar.is_planar() || planar.n() + planar.f() != planar.m() + 2 {
+        debug(format!(
+            "The embedding has genus {} and {} faces, so we fall back to the exact search for a directed diversion.",
+            planar.genus(),
+            planar.f()
+        ));
+        return Curve::Bound(0.into());
+    }
+
+    let Some((bound, curve)) = shortest_winding_curve(graph, &path, &b) else {
+        return Curve::Impossible;
+    };
+    let mut cut: Vec<PlanarEdge<W>> = Vec::new();
+    for d in &curve {
+        if let Some(a) = graph.crossed(d) {
+            if !cut.contains(a) {
+                cut.push(a.clone());
+            }
+        }
+    }
+
+    let mut diverted = real.clone();
+    diverted.delete_arcs(&cut);
+    if directed_bfs(&diverted, s, t, (du, dv)).is_none() && through_diversion(&diverted, s, t, &b) {
+        let cost = cut.iter().fold(0.into(), |acc, a| acc + a.weight());
+        debug(format!(
+            "We have to cut {} arcs to divert the network, with a total cost of {}.",
+            cut.len(),
+            cost
+        ));
+        return Curve::Diversion((cost, cut));
+    }
+
+    debug(format!(
+        "The cheapest curve of cost {} does not leave a path through the diversion arc, so we fall back to the exact search.",
+        bound
+    ));
+    Curve::Bound(bound)
+}
+
+/// A face of the dual, together with the signed number of times the curve has crossed P so far.
+type Winding = (usize, i64);
+
+/// The dual arcs of the cheapest closed curve through b* that crosses P once more from right to left than from left to right.
+fn shortest_winding_curve<W: Weight>(
+    graph: &DirectedPlanarGraph<W>,
+    path: &[PlanarEdge<W>],
+    b: &PlanarEdge<W>,
+) -> Option<(W, Vec<PlanarEdge<W>>)> {
+    let mut crossing: HashMap<(usize, usize), i64> = HashMap::new();
+    for e in path {
+        crossing.insert((e.from(), e.to()), 1);
+        crossing.insert((e.to(), e.from()), -1);
+    }
+    // A simple curve crosses every edge of P at most once.
+    let limit = path.len() as i64 + 1;
+    let is_b = |d: &PlanarEdge<W>| {
+        (d.left(), d.right()) == (b.from(), b.to()) || (d.left(), d.right()) == (b.to(), b.from())
+    };
+
+    let start = (b.left(), 0);
+    let target = (b.right(), 1);
+    let mut dist: HashMap<Winding, Cost<W>> = HashMap::new();
+    let mut pred: HashMap<Winding, (Winding, PlanarEdge<W>)> = HashMap::new();
+    let mut pq = BinaryHeap::new();
+    dist.insert(start, Finite(0.into()));
+    pq.push(Reverse((Finite(0.into()), start)));
+
+    while let Some(Reverse((d_u, u))) = pq.pop() {
+        if dist.get(&u).is_some_and(|&d| d < d_u) {
+            continue;
+        }
+        if u == target {
+            break;
+        }
+        for d in graph.dual().N(u.0) {
+            let level = u.1 + crossing.get(&(d.left(), d.right())).unwrap_or(&0);
+            if is_b(d) || level.abs() > limit {
+                continue;
+            }
+            let v = (d.to(), level);
+            let d_v = d_u + Finite(d.weight());
+            if d_v < *dist.get(&v).unwrap_or(&Infinite) {
+                dist.insert(v, d_v);
+                pred.insert(v, (u, d.clone()));
+                pq.push(Reverse((d_v, v)));
+            }
+        }
+    }
+
+    let bound: Option<W> = (*dist.get(&target)?).into();
+    let bound = bound?;
+    let mut curve = Vec::new();
+    let mut curr = target;
+    while curr != start {
+        let (prev, d) = pred[&curr].clone();
+        curve.push(d);
+        curr = prev;
+    }
+    curve.reverse();
+    Some((bound, curve))
+}
+
+/**
+Problem: Directed Network Diversion (exact, exponential)
+In: any directed graph G, two vertices s,t in V(G), a diversion arc b = (du, dv), and a lower bound on the optimum
+Out: the same as `directed_network_diversion`.
+
+Branch and bound: some arc of every directed s-t-path that avoids b must be cut, so we branch on which arc of such a path is cut,
+and the arcs before it on the path are kept in that branch. Cutting more arcs never brings back a path through b,
+so branches where no path through b is left are pruned. After `BRANCH_LIMIT` branches we give up.
+*/
+pub fn exact_directed_diversion<W: Weight>(
+    real: &DirectedGraph<W, PlanarEdge<W>>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+    lower_bound: W,
+) -> Result<Diversion<W>, DirectedDiversionError> {
+    let b = real.arc(du, dv);
+    let mut best = None;
+    let mut branches = 0;
+    branch(
+        real,
+        s,
+        t,
+        &b,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        0.into(),
+        lower_bound,
+        &mut best,
+        &mut branches,
+    );
+    if branches > BRANCH_LIMIT {
+        debug(format!(
+            "The exact search gave up after {} branches.",
+            BRANCH_LIMIT
+        ));
+        return Err(DirectedDiversionError::GaveUp(BRANCH_LIMIT));
+    }
+    best.ok_or(DirectedDiversionError::NoDiversion)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn branch<W: Weight>(
+    real: &DirectedGraph<W, PlanarEdge<W>>,
+    s: usize,
+    t: usize,
+    b: &PlanarEdge<W>,
+    cut: &mut Vec<PlanarEdge<W>>,
+    kept: &mut Vec<(usize, usize)>,
+    cost: W,
+    lower_bound: W,
+    best: &mut Option<Diversion<W>>,
+    branches: &mut usize,
+) {
+    *branches += 1;
+    if *branches > BRANCH_LIMIT {
+        return;
+    }
+    if best
+        .as_ref()
+        .is_some_and(|(c, _)| *c <= cost || *c <= lower_bound)
+    {
+        return;
+    }
+    let mut diverted = real.clone();
+    diverted.delete_arcs(cut);
+    if !through_diversion(&diverted, s, t, b) {
+        return;
+    }
+    let path = match directed_bfs(&diverted, s, t, (b.from(), b.to())) {
+        None => {
+            *best = Some((cost, cut.clone()));
+            return;
+        }
+        Some(path) => path,
+    };
+
+    let before = kept.len();
+    for a in path.iter().rev() {
+        if kept.contains(&(a.from(), a.to())) {
+            continue;
+        }
+        cut.push(a.clone());
+        branch(
+            real,
+            s,
+            t,
+            b,
+            cut,
+            kept,
+            cost + a.weight(),
+            lower_bound,
+            best,
+            branches,
+        );
+        cut.pop();
+        kept.push((a.from(), a.to()));
+    }
+    kept.truncate(before);
+}
+
+/// Whether some directed s-t-path uses b, that is, s reaches du and dv reaches t without b.
+fn through_diversion<W: Weight, E: Edge<W>>(
+    graph: &DirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+    b: &E,
+) -> bool {
+    let reaches =
+        |u: usize, v: usize| u == v || directed_bfs(graph, u, v, (b.from(), b.to())).is_some();
+    reaches(s, b.from()) && reaches(b.to(), t)
+}
+
+/// The directed counterpart of `bfs`: the arcs of a directed s-t-path that avoids the arc du -> dv,
+/// in order from t back to s.
+pub(crate) fn directed_bfs<W: Weight, E: Edge<W>>(
+    graph: &DirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+) -> Option<Vec<E>> {
+    let mut seen = repeat(graph.n(), false);
+    let mut prev: Vec<Option<E>> = repeat(graph.n(), None);
+    let mut q: Queue<usize> = Queue::new();
+    seen[s] = true;
+    q.add(s).ok()?;
+
+    while let Ok(u) = q.remove() {
+        for arc in graph.N(u) {
+            let v = arc.to();
+            if (u, v) != (du, dv) && !seen[v] {
+                seen[v] = true;
+                q.add(v).ok()?;
+                prev[v] = Some(arc.clone());
+            }
+        }
+        if seen[t] {
+            break;
+        }
+    }
+
+    if s != t && seen[t] {
+        let mut ret: Vec<E> = vec![prev[t].clone().unwrap()];
+        let mut curr = ret[0].clone();
+        while curr.from() != s {
+            curr = prev[curr.from()].clone().unwrap();
+            ret.push(curr.clone());
+        }
+        return Some(ret);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_directed_diversion {
+    use crate::algorithm::directed_diversion::{
+        directed_network_diversion, exact_directed_diversion, winding_diversion, Curve,
+        DirectedDiversionError,
+    };
+    use crate::algorithm::network_diversion::network_diversion;
+    use crate::algorithm::test_utility::random_grid;
+    use crate::structure::graph::directed_planar_graph::DirectedPlanarGraph;
+    use crate::structure::graph::edge::Edge;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_two_way_streets_match_undirected() {
+        let text = std::fs::read_to_string("data/example.in").unwrap();
+        let planar: PlanarGraph<f64> = text.parse().unwrap();
+        let arcs: Vec<_> = planar
+            .real()
+            .edges()
+            .iter()
+            .map(|e| (e.from(), e.to(), e.weight))
+            .collect();
+        let directed = DirectedPlanarGraph::from_arcs(text.parse().unwrap(), &arcs).unwrap();
+
+        let (cost, cut) = directed_network_diversion(&directed, 0, 9, (7, 8)).unwrap();
+        assert_eq!(
+            Some(cost),
+            network_diversion(&planar, 0, 9, (7, 8)).map(|(cost, _)| cost)
+        );
+        assert_eq!(cut.len(), 3);
+        // Every path through 8 -> 7 has to come back to 2 to reach 9.
+        assert_eq!(
+            directed_network_diversion(&directed, 0, 9, (8, 7)).err(),
+            Some(DirectedDiversionError::NoDiversion)
+        );
+    }
+
+    #[test]
+    fn test_one_way_streets_match_exact() {
+        let mut random = Random::new(5);
+        let mut settled = 0;
+        for _ in 0..40 {
+            let planar: PlanarGraph<u64> = random_grid(&mut random, 4, 4, 9);
+            let mut arcs = Vec::new();
+            // A quarter of the streets are one-way, so that most arcs lie on some directed path.
+            for e in planar
+                .real()
+                .edges()
+                .into_iter()
+                .filter(|e| e.from() < e.to())
+            {
+                let (u, v, w) = (e.from(), e.to(), e.weight);
+                match random.below(8) {
+                    0 => arcs.push((u, v, w)),
+                    1 => arcs.push((v, u, w)),
+                    _ => arcs.extend([(u, v, w), (v, u, w)]),
+                }
+            }
+            let graph = DirectedPlanarGraph::from_arcs(planar, &arcs).unwrap();
+            let (u, v, _) = arcs[random.below(arcs.len())];
+
+            let found = directed_network_diversion(&graph, 0, 15, (u, v)).map(|(c, _)| c);
+            let exact = exact_directed_diversion(graph.real(), 0, 15, (u, v), 0).map(|(c, _)| c);
+            assert_eq!(found, exact);
+            // Only the instances that the curve settles on its own compare two different solvers.
+            if let Curve::Diversion((cost, cut)) = winding_diversion(&graph, 0, 15, (u, v)) {
+                assert_eq!(Ok(cost), exact);
+                if !cut.is_empty() {
+                    settled += 1;
+                }
+            }
+        }
+        assert!(settled >= 10, "The curve settled only {} of 40", settled);
+    }
+}
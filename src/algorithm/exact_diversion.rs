@@ -0,0 +1,353 @@
+use crate::algorithm::directed_diversion::BRANCH_LIMIT;
+use crate::algorithm::max_flow::min_cut;
+use crate::algorithm::network_diversion::bfs;
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::weight::Weight;
+use crate::utility::misc::{debug, repeat};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum ExactDiversionError {
+    /// No set of unprotected edges leaves only s-t-paths through b.
+    NoDiversion,
+    /// The search gave up after this many branches, before it could prove an answer.
+    GaveUp(usize),
+}
+
+impl Display for ExactDiversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExactDiversionError::NoDiversion => write!(f, "No cut found"),
+            ExactDiversionError::GaveUp(limit) => write!(
+                f,
+                "The exact search gave up after {} branches, before it found the cheapest diversion",
+                limit
+            ),
+        }
+    }
+}
+
+/**
+Problem: Network Diversion (exact, any graph)
+In: any graph G, two vertices s,t in V(G), a diversion edge b = (du, dv), and which edges are protected
+Out: a minimum-cost set of unprotected edges X such that every s-t-path in G - X uses b,
+     while some s-t-path in G - X still exists, or an error if no such set exists or the search gave up.
+
+If the paths in G - X use b from du to dv, the component S of s in G - X - b is connected, contains du but not dv and t,
+and dv still reaches t outside S, so X is the boundary of S without b. We branch on the side of the vertices,
+starting from {s, du} in S and {t, dv} outside, and the other way around. A minimum cut between the two sides
+in G - b is a lower bound, and when the component of s on its side is such an S, that component is the best one.
+Otherwise some vertex just outside that component connects it to a vertex that has to be in S,
+or some vertex just inside connects dv to t, and we branch on the side of that vertex.
+The two ends of a protected edge are always on the same side, so when the boundary of the component still has a
+protected edge, we branch on the side of one of its ends. After `BRANCH_LIMIT` branches we give up.
+*/
+pub fn exact_network_diversion<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+    protected: &dyn Fn(&E) -> bool,
+) -> Result<(W, Vec<E>), ExactDiversionError> {
+    let b = graph.edge(du, dv);
+    if bfs(graph, s, t, (du, dv)).is_none() {
+        debug("Could not find any s-t-path that doesn't use the diversion edge, no diversion is needed.".to_string());
+        return Ok((0.into(), Vec::new()));
+    }
+
+    let mut search = Search {
+        graph,
+        s,
+        t,
+        b,
+        protected,
+        best: None,
+        branches: 0,
+    };
+    for (from, to) in [(du, dv), (dv, du)] {
+        if ![t, to].contains(&s) && ![t, to].contains(&from) {
+            let mut side = repeat(graph.n(), None);
+            side[s] = Some(true);
+            side[from] = Some(true);
+            side[t] = Some(false);
+            side[to] = Some(false);
+            search.branch(side, to);
+        }
+    }
+    if search.branches > BRANCH_LIMIT {
+        debug(format!(
+            "The exact search gave up after {} branches.",
+            BRANCH_LIMIT
+        ));
+        return Err(ExactDiversionError::GaveUp(BRANCH_LIMIT));
+    }
+    debug(format!(
+        "The exact search took {} branches, and found {}.",
+        search.branches,
+        match &search.best {
+            Some((cost, cut)) =>
+                format!("a diversion set of {} edges with cost {}", cut.len(), cost),
+            None => "no diversion set".to_string(),
+        }
+    ));
+    search.best.ok_or(ExactDiversionError::NoDiversion)
+}
+
+struct Search<'a, W: Weight, E: Edge<W>> {
+    graph: &'a UndirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+    b: E,
+    protected: &'a dyn Fn(&E) -> bool,
+    best: Option<(W, Vec<E>)>,
+    branches: usize,
+}
+
+impl<W: Weight, E: Edge<W>> Search<'_, W, E> {
+    fn is_b(&self, e: &E) -> bool {
+        let b = (self.b.from(), self.b.to());
+        (e.from(), e.to()) == b || (e.to(), e.from()) == b
+    }
+
+    /// Puts the two ends of every protected edge on the same side, or returns false if they have to be apart.
+    fn close_protected(&self, side: &mut [Option<bool>]) -> bool {
+        let mut stack: Vec<usize> = self
+            .graph
+            .vertices()
+            .filter(|&u| side[u].is_some())
+            .collect();
+        while let Some(u) = stack.pop() {
+            for e in self.graph.N(u) {
+                if !(self.protected)(e) || self.is_b(e) {
+                    continue;
+                }
+                match side[e.to()] {
+                    None => {
+                        side[e.to()] = side[u];
+                        stack.push(e.to());
+                    }
+                    Some(other) if Some(other) != side[u] => return false,
+                    Some(_) => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// For every vertex that the start reaches in G - b through the open edges, the edge it was reached by.
+    fn reach(&self, start: usize, open: &dyn Fn(&E) -> bool) -> Vec<Option<Option<E>>> {
+        let mut prev: Vec<Option<Option<E>>> = repeat(self.graph.n(), None);
+        prev[start] = Some(None);
+        let mut stack = vec![start];
+        while let Some(u) = stack.pop() {
+            for e in self.graph.N(u) {
+                if prev[e.to()].is_none() && !self.is_b(e) && open(e) {
+                    prev[e.to()] = Some(Some(e.clone()));
+                    stack.push(e.to());
+                }
+            }
+        }
+        prev
+    }
+
+    /// The vertex closest to the start, on the path that `reach` found to v, that is picked.
+    fn first_on_path(prev: &[Option<Option<E>>], v: usize, pick: &dyn Fn(usize) -> bool) -> usize {
+        let mut first = v;
+        let mut curr = v;
+        while let Some(Some(e)) = &prev[curr] {
+            curr = e.from();
+            if pick(curr) {
+                first = curr;
+            }
+        }
+        first
+    }
+
+    /// `side[v]` is Some(true) if v has to be in S, and Some(false) if it has to be outside,
+    /// where the paths use b towards `to` outside S.
+    fn branch(&mut self, mut side: Vec<Option<bool>>, to: usize) {
+        self.branches += 1;
+        if self.branches > BRANCH_LIMIT || !self.close_protected(&mut side) {
+            return;
+        }
+        let sources: Vec<usize> = self
+            .graph
+            .vertices()
+            .filter(|&u| side[u] == Some(true))
+            .collect();
+        let sinks: Vec<usize> = self
+            .graph
+            .vertices()
+            .filter(|&u| side[u] == Some(false))
+            .collect();
+        let (bound, cut) = min_cut(self.graph, &sources, &sinks, &|e| {
+            (!self.is_b(e)).then(|| e.weight())
+        });
+        if self.best.as_ref().is_some_and(|(c, _)| *c <= bound) {
+            return;
+        }
+        let in_cut = |e: &E| {
+            cut.iter().any(|c| {
+                (c.from(), c.to()) == (e.from(), e.to()) || (c.to(), c.from()) == (e.from(), e.to())
+            })
+        };
+        let inside: Vec<bool> = self
+            .reach(self.s, &|e| !in_cut(e))
+            .iter()
+            .map(Option::is_some)
+            .collect();
+
+        let vertex = if let Some(&missing) = sources.iter().find(|&&u| !inside[u]) {
+            // S has to connect s to every vertex that has to be in S, through vertices that may be in S.
+            let prev = self.reach(self.s, &|e| side[e.to()] != Some(false));
+            if prev[missing].is_none() {
+                return;
+            }
+            Self::first_on_path(&prev, missing, &|u| !inside[u])
+        } else {
+            let outside = self.reach(to, &|e| !inside[e.to()]);
+            if outside[self.t].is_some() {
+                let boundary: Vec<E> = self
+                    .graph
+                    .edges()
+                    .into_iter()
+                    .filter(|e| {
+                        e.from() < e.to() && inside[e.from()] != inside[e.to()] && !self.is_b(e)
+                    })
+                    .collect();
+                // A protected edge on the boundary has two free ends, since the closed sides keep them together.
+                match boundary.iter().find(|e| (self.protected)(e)) {
+                    Some(e) => e.from(),
+                    None => {
+                        self.best = Some((bound, boundary));
+                        return;
+                    }
+                }
+            } else {
+                // Outside of S, dv has to reach t, through vertices that may be outside.
+                let prev = self.reach(to, &|e| side[e.to()] != Some(true));
+                if prev[self.t].is_none() {
+                    return;
+                }
+                Self::first_on_path(&prev, self.t, &|u| inside[u])
+            }
+        };
+
+        let first = if inside[vertex] {
+            [false, true]
+        } else {
+            [true, false]
+        };
+        for choice in first {
+            let mut side = side.clone();
+            side[vertex] = Some(choice);
+            self.branch(side, to);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exact_diversion {
+    use crate::algorithm::exact_diversion::exact_network_diversion;
+    use crate::algorithm::network_diversion::network_diversion;
+    use crate::algorithm::set_diversion::exact_set_diversion;
+    use crate::algorithm::test_utility::random_grid_with_edge;
+    use crate::structure::graph::edge::{BasicEdge, Edge};
+    use crate::structure::graph::undirected_graph::UndirectedGraph;
+    use crate::structure::weight::Weighted;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_matches_planar() {
+        let mut random = Random::new(31);
+        for _ in 0..30 {
+            let (planar, (u, v)) = random_grid_with_edge::<u64>(&mut random, 5, 4, 9);
+
+            let exact = exact_network_diversion(planar.real(), 0, 19, (u, v), &|_| false);
+            let planar_result = network_diversion(&planar, 0, 19, (u, v));
+            assert_eq!(exact.ok().map(|(c, _)| c), planar_result.map(|(c, _)| c));
+        }
+    }
+
+    #[test]
+    fn test_non_planar_matches_all_cuts() {
+        let mut random = Random::new(37);
+        for _ in 0..30 {
+            // A random graph on 10 vertices, which is rarely planar.
+            let mut text = "10\n".to_string();
+            for u in 0..10 {
+                for v in u + 1..10 {
+                    if random.below(5) < 2 {
+                        text += &format!("{} {} {}\n", u, v, 1 + random.below(9));
+                    }
+                }
+            }
+            let graph: UndirectedGraph<u64, BasicEdge<u64>> = text.parse().unwrap();
+            let edges: Vec<_> = graph
+                .edges()
+                .into_iter()
+                .filter(|e| e.from() < e.to())
+                .collect();
+            let b = edges[random.below(edges.len())].clone();
+
+            let exact = exact_network_diversion(&graph, 0, 9, (b.from(), b.to()), &|_| false);
+            let all_cuts = exact_set_diversion(&graph, 0, 9, &[b]);
+            match exact {
+                Ok((cost, cut)) if !cut.is_empty() => {
+                    assert_eq!(Ok(cost), all_cuts.map(|(c, _)| c))
+                }
+                // No s-t-path avoids b, or none exists at all.
+                _ => assert!(all_cuts.is_err() || all_cuts.is_ok_and(|(c, _)| c == 0)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_protected_matches_heavy_edges() {
+        let mut random = Random::new(42);
+        for _ in 0..30 {
+            let mut text = "9\n".to_string();
+            for u in 0..9 {
+                for v in u + 1..9 {
+                    if random.below(5) < 2 {
+                        text += &format!("{} {} {}\n", u, v, 1 + random.below(9));
+                    }
+                }
+            }
+            let graph: UndirectedGraph<u64, BasicEdge<u64>> = text.parse().unwrap();
+            let edges: Vec<_> = graph
+                .edges()
+                .into_iter()
+                .filter(|e| e.from() < e.to())
+                .collect();
+            let b = edges[random.below(edges.len())].clone();
+            let protected: Vec<_> = (0..2)
+                .map(|_| edges[random.below(edges.len())].clone())
+                .collect();
+            let is_protected =
+                |e: &BasicEdge<u64>| protected.contains(e) || protected.contains(&e.reverse());
+
+            // A protected edge that costs more than all the others together is only cut when it has to be.
+            let heavy = edges.iter().map(|e| e.weight()).sum::<u64>() + 1;
+            let weighted = graph.map_edges(|e| {
+                if is_protected(e) && *e != b && *e != b.reverse() {
+                    BasicEdge::new(e.from(), e.to(), heavy)
+                } else {
+                    e.clone()
+                }
+            });
+            let expected = exact_network_diversion(&weighted, 0, 8, (b.from(), b.to()), &|_| false)
+                .ok()
+                .filter(|(cost, _)| *cost < heavy);
+            let found = exact_network_diversion(&graph, 0, 8, (b.from(), b.to()), &is_protected);
+            assert_eq!(
+                found.as_ref().ok().map(|(c, _)| *c),
+                expected.map(|(c, _)| c)
+            );
+            if let Ok((_, cut)) = found {
+                assert!(cut.iter().all(|e| !is_protected(e)));
+            }
+        }
+    }
+}
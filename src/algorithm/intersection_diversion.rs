@@ -0,0 +1,346 @@
+use crate::algorithm::k_best_diversion::odd_path_alternatives;
+use crate::algorithm::network_diversion::{bfs, network_diversion, DiversionError};
+use crate::algorithm::utility::split_edges;
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::weight::Weight;
+use crate::utility::misc::debug;
+
+/// The number of curves that are tried before we settle for closing roads only.
+pub const CURVE_LIMIT: usize = 1000;
+
+pub struct IntersectionDiversion<W: Weight> {
+    pub cost: W,
+    /// The roads that are closed.
+    pub edges: Vec<PlanarEdge<W>>,
+    /// The intersections that are closed, together with all of their roads.
+    pub vertices: Vec<usize>,
+    /// Whether the cost is optimal, or no valid curve was among the first `CURVE_LIMIT`,
+    /// so only roads are closed, as in `network_diversion`.
+    pub exact: bool,
+}
+
+/**
+Problem: Planar Network Diversion with intersection closures
+In: a planar graph G, two vertices s,t in V(G), a diversion edge b in E(G), and a cost c(v) for every vertex v
+    that may be closed, or None for the vertices that may not
+Out: a minimum-cost set of edges X and vertices Y, such that every s-t-path in G - X - Y uses b,
+     while some s-t-path in G - X - Y still exists, or an error if no such sets exist or the embedding is not planar.
+
+The cut is a closed curve through b* that separates s from t, and that passes either across an edge e, for w(e),
+or through a vertex v, for c(v), from any face around v to any other. So we add a dual vertex x_v for every v that
+may be closed, with links of weight c(v) to the faces around v, and double the weight of the dual edges, since the curve
+passes through x_v on two links. Passing through a vertex v of the s-t-path P crosses P exactly when the curve enters
+and leaves v on different sides of P, so the links to the faces on one side of P count as crossings.
+Unlike a cut of edges, such a curve may leave no path through b, so we take the cheapest valid curve from
+`odd_path_alternatives`. After `CURVE_LIMIT` curves we give up on closing vertices, and close the roads of
+`network_diversion` instead. The vertices s and t and the endpoints of b are never closed.
+*/
+pub fn intersection_diversion<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    b: (usize, usize),
+    vertex_costs: &[Option<W>],
+) -> Result<IntersectionDiversion<W>, DiversionError> {
+    if !planar.is_planar() {
+        return Err(DiversionError::NotPlanar(planar.genus()));
+    }
+    limited_intersection_diversion(planar, s, t, b, vertex_costs, CURVE_LIMIT)
+        .ok_or(DiversionError::NoDiversion)
+}
+
+fn limited_intersection_diversion<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+    vertex_costs: &[Option<W>],
+    limit: usize,
+) -> Option<IntersectionDiversion<W>> {
+    let p = match bfs(planar.real(), s, t, (du, dv)) {
+        Some(p) => p,
+        None => {
+            debug("Could not find any s-t-path that doesn't use the diversion edge, no diversion is needed.".to_string());
+            return Some(IntersectionDiversion {
+                cost: 0.into(),
+                edges: Vec::new(),
+                vertices: Vec::new(),
+                exact: true,
+            });
+        }
+    };
+    let diversion = planar.real().edge(du, dv);
+
+    let f = planar.f();
+    let double = |e: &PlanarEdge<W>| e.with_weight(e.weight + e.weight);
+    let mut dual = UndirectedGraph::new(f + planar.n());
+    for e in planar.dual().edges() {
+        if e.from() < e.to() {
+            dual.add_edge(double(&e));
+        }
+    }
+    let mut marked: Vec<PlanarEdge<W>> = p.iter().map(|e| double(&e.rotate_right())).collect();
+    for v in planar.real().vertices() {
+        let cost = match vertex_costs.get(v).copied().flatten() {
+            Some(cost) if ![s, t, du, dv].contains(&v) => cost,
+            _ => continue,
+        };
+        // The path P runs from t back to s, so the edge at v towards t comes first.
+        let side = p
+            .iter()
+            .position(|e| e.from() == v)
+            .map(|i| one_side(planar, v, &p[i], &p[i + 1]));
+        let mut faces = Vec::new();
+        for e in planar.real().N(v) {
+            if faces.contains(&e.left()) {
+                continue;
+            }
+            faces.push(e.left());
+            let mut link = e.rotate_right();
+            link.to = f + v;
+            link.weight = cost;
+            if side.as_ref().is_some_and(|side| side.contains(&e.left())) {
+                marked.push(link.clone());
+            }
+            dual.add_edge(link);
+        }
+    }
+    let (split, map) = split_edges(&dual, marked);
+
+    // A curve through a closed vertex v may enclose vertices that are only connected to s through v, so that no path
+    // through b is left. The alternatives come in order of cost, and every curve is among them, so the first valid
+    // curve is optimal.
+    let curves = odd_path_alternatives(split, map, diversion.left(), diversion.right());
+    for (i, (_, curve)) in curves.enumerate() {
+        if i == limit {
+            debug(format!(
+                "None of the first {} curves leaves a path through b, so we only close roads.",
+                limit
+            ));
+            let (cost, edges) = network_diversion(planar, s, t, (du, dv))?;
+            return Some(IntersectionDiversion {
+                cost,
+                edges,
+                vertices: Vec::new(),
+                exact: false,
+            });
+        }
+        let mut edges = Vec::new();
+        let mut vertices = Vec::new();
+        for e in curve {
+            // Every closed vertex x_v is passed on two links.
+            let x = e.from().max(e.to());
+            if x < f {
+                let e = e.rotate_right();
+                edges.push(e.with_weight(e.weight / W::from(2)));
+            } else if !vertices.contains(&(x - f)) {
+                vertices.push(x - f);
+            }
+        }
+        if diverts(planar.real(), s, t, &diversion, &edges, &vertices) {
+            let cost = closure_cost(&edges, &vertices, vertex_costs);
+            debug(format!(
+                "We have to close {} edges and {} vertices to divert the network, with a total cost of {}, after {} curves that leave no path through b.",
+                edges.len(),
+                vertices.len(),
+                cost,
+                i
+            ));
+            return Some(IntersectionDiversion {
+                cost,
+                edges,
+                vertices,
+                exact: true,
+            });
+        }
+    }
+    debug(format!(
+        "No diversion set exist, no paths from {} to {} go through ({}, {}).",
+        s, t, du, dv
+    ));
+    None
+}
+
+fn closure_cost<W: Weight, E: Edge<W>>(
+    edges: &[E],
+    vertices: &[usize],
+    vertex_costs: &[Option<W>],
+) -> W {
+    vertices
+        .iter()
+        .filter_map(|&v| vertex_costs[v])
+        .chain(edges.iter().map(|e| e.weight()))
+        .fold(0.into(), |acc, w| acc + w)
+}
+
+/// Whether some s-t-path is left after the closures, and every such path uses b.
+fn diverts<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+    b: &E,
+    edges: &[E],
+    vertices: &[usize],
+) -> bool {
+    let same = |e: &E, f: &E| {
+        (e.from(), e.to()) == (f.from(), f.to()) || (e.to(), e.from()) == (f.from(), f.to())
+    };
+    let closed = |e: &E| {
+        vertices.contains(&e.from())
+            || vertices.contains(&e.to())
+            || edges.iter().any(|c| same(c, e))
+    };
+    let mut open = graph.clone();
+    open.delete_edges(&graph.edges().into_iter().filter(closed).collect());
+    // There are no loops in G, so (s, s) is not an edge, and this is a plain search.
+    bfs(&open, s, t, (s, s)).is_some() && bfs(&open, s, t, (b.from(), b.to())).is_none()
+}
+
+/// The faces around v on one side of the path through the edges `towards_t` and `towards_s` at v.
+/// The other edges at v join the faces on their two sides, and the two path edges split them into two sides.
+fn one_side<W: Weight>(
+    planar: &PlanarGraph<W>,
+    v: usize,
+    towards_t: &PlanarEdge<W>,
+    towards_s: &PlanarEdge<W>,
+) -> Vec<usize> {
+    let on_path = |e: &PlanarEdge<W>| e.to() == towards_t.to() || e.to() == towards_s.from();
+    let mut side = vec![towards_t.left()];
+    let mut grown = true;
+    while grown {
+        grown = false;
+        for e in planar.real().N(v).iter().filter(|e| !on_path(e)) {
+            for (a, b) in [(e.left(), e.right()), (e.right(), e.left())] {
+                if side.contains(&a) && !side.contains(&b) {
+                    side.push(b);
+                    grown = true;
+                }
+            }
+        }
+    }
+    side
+}
+
+#[cfg(test)]
+mod test_intersection_diversion {
+    use crate::algorithm::intersection_diversion::{
+        intersection_diversion, limited_intersection_diversion,
+    };
+    use crate::algorithm::network_diversion::network_diversion;
+    use crate::algorithm::test_utility::{random_grid_with_edge, sides};
+    use crate::structure::graph::edge::Edge;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::utility::random::Random;
+
+    /// The cheapest closures, over all closed vertices Y and all sides S of s in G - Y.
+    fn brute_force(
+        planar: &PlanarGraph<u64>,
+        s: usize,
+        t: usize,
+        b: (usize, usize),
+        costs: &[Option<u64>],
+    ) -> Option<u64> {
+        let n = planar.n();
+        let edges: Vec<_> = planar
+            .real()
+            .edges()
+            .into_iter()
+            .filter(|e| e.from() < e.to())
+            .collect();
+        let is_b = |u: usize, v: usize| (u, v) == b || (v, u) == b;
+        let connected = |open: &dyn Fn(usize, usize) -> bool| {
+            let mut seen = vec![false; n];
+            let mut stack = vec![s];
+            seen[s] = true;
+            while let Some(u) = stack.pop() {
+                for e in planar.real().N(u) {
+                    if !seen[e.to()] && open(u, e.to()) {
+                        seen[e.to()] = true;
+                        stack.push(e.to());
+                    }
+                }
+            }
+            seen[t]
+        };
+
+        let all_sides: Vec<_> = sides(n, s, t).collect();
+        let mut best = None;
+        for closed in 0..1usize << n {
+            let is_closed = |u: usize| closed >> u & 1 == 1;
+            if (0..n).any(|u| is_closed(u) && costs[u].is_none()) {
+                continue;
+            }
+            let base: u64 = (0..n)
+                .filter(|&u| is_closed(u))
+                .map(|u| costs[u].unwrap())
+                .sum();
+            for side in &all_sides {
+                if (0..n).any(|u| side[u] && is_closed(u)) || side[b.0] == side[b.1] {
+                    continue;
+                }
+                let cut = |u: usize, v: usize| {
+                    !is_closed(u) && !is_closed(v) && side[u] != side[v] && !is_b(u, v)
+                };
+                let cost = base
+                    + edges
+                        .iter()
+                        .filter(|e| cut(e.from(), e.to()))
+                        .map(|e| e.weight)
+                        .sum::<u64>();
+                if best.is_some_and(|c| c <= cost) {
+                    continue;
+                }
+                let open = |u: usize, v: usize| !is_closed(u) && !is_closed(v) && !cut(u, v);
+                let avoiding = |u: usize, v: usize| open(u, v) && !is_b(u, v);
+                if connected(&open) && !connected(&avoiding) {
+                    best = Some(cost);
+                }
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_without_vertex_costs_matches_network_diversion() {
+        let planar: PlanarGraph<f64> = std::fs::read_to_string("data/example.in")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let closed = intersection_diversion(&planar, 0, 9, (7, 8), &[None; 10]).unwrap();
+        assert!(closed.vertices.is_empty());
+        assert_eq!(
+            Some(closed.cost),
+            network_diversion(&planar, 0, 9, (7, 8)).map(|(cost, _)| cost)
+        );
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut random = Random::new(29);
+        for _ in 0..30 {
+            let (planar, (u, v)) = random_grid_with_edge::<u64>(&mut random, 4, 3, 9);
+            let costs: Vec<Option<u64>> = (0..12)
+                .map(|w| {
+                    let cost = 1 + random.below(12) as u64;
+                    (w != 0 && w != 11 && w != u && w != v && cost < 10).then_some(cost)
+                })
+                .collect();
+
+            let found = intersection_diversion(&planar, 0, 11, (u, v), &costs).ok();
+            assert!(found.as_ref().is_none_or(|d| d.exact));
+            assert_eq!(
+                found.map(|d| d.cost),
+                brute_force(&planar, 0, 11, (u, v), &costs)
+            );
+
+            // Without any curves to try, only the roads of the network diversion are closed.
+            let roads = limited_intersection_diversion(&planar, 0, 11, (u, v), &costs, 0);
+            let expected = network_diversion(&planar, 0, 11, (u, v)).map(|(cost, _)| cost);
+            assert_eq!(roads.as_ref().map(|d| d.cost), expected);
+            assert!(roads.is_none_or(|d| d.vertices.is_empty()));
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use crate::algorithm::network_diversion::{bfs, DiversionError};
+use crate::algorithm::odd_path::{shortest_even_path, shortest_odd_path};
+use crate::algorithm::utility::split_edges;
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::path_result::PathResult::*;
+use crate::structure::weight::Weight;
+use crate::utility::misc::{debug, repeat};
+
+type Diversion<W> = (W, Vec<PlanarEdge<W>>);
+
+/**
+Problem: k-best Planar Network Diversion
+In: a planar graph G, two vertices s,t in V(G), a diversion edge b in E(G), and a number k
+Out: the k cheapest distinct minimal diversion sets, in order of cost, each with its cost,
+     or an error if the embedding is not planar.
+
+Fewer than k sets are returned when there are no more, see `diversion_alternatives`.
+*/
+pub fn k_best_diversions<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+    k: usize,
+) -> Result<Vec<Diversion<W>>, DiversionError> {
+    let found: Vec<_> = diversion_alternatives(planar, s, t, (du, dv))?
+        .take(k)
+        .collect();
+    debug(format!(
+        "Found {} alternative diversion sets for ({}, {}).",
+        found.len(),
+        du,
+        dv
+    ));
+    Ok(found)
+}
+
+/**
+All minimal diversion sets for b, lazily and in order of cost.
+
+The minimal diversion sets are exactly the simple odd left(b)-right(b)-paths in the split dual,
+so we enumerate those with Yen's algorithm: every next path deviates from an earlier one at some spur vertex,
+after a root that it shares with it. The spur has to complete the parity of the root, so it is found
+with `shortest_odd_path` or `shortest_even_path` in the split dual without the root and the earlier deviations.
+When no s-t-path avoids b, the empty set is the only minimal diversion set.
+Off the plane, a dual path need not be a cut, so we return an error instead.
+*/
+pub fn diversion_alternatives<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+) -> Result<impl Iterator<Item = (W, Vec<PlanarEdge<W>>)>, DiversionError> {
+    if !planar.is_planar() {
+        return Err(DiversionError::NotPlanar(planar.genus()));
+    }
+    let diversion = planar.real().edge(du, dv);
+    let p = bfs(planar.real(), s, t, (du, dv));
+    let marked = p.iter().flatten().map(|e| e.rotate_right()).collect();
+    let (split, map) = split_edges(planar.dual(), marked);
+
+    let mut alternatives = odd_path_alternatives(split, map, diversion.left(), diversion.right());
+    if p.is_none() {
+        alternatives.candidates = vec![(0.into(), Vec::new())];
+    }
+    Ok(alternatives.map(|(cost, dual)| (cost, dual.iter().map(|e| e.rotate_right()).collect())))
+}
+
+/// All simple odd source-target-paths in the split graph, lazily and in order of cost, mapped back by `map`
+/// to the graph that was split. Paths that map to the same edges are only returned once.
+pub(crate) fn odd_path_alternatives<W, E, F>(
+    split: UndirectedGraph<W, E>,
+    map: F,
+    source: usize,
+    target: usize,
+) -> Alternatives<W, E, F>
+where
+    W: Weight,
+    E: Edge<W>,
+    F: Fn(&E) -> Option<E>,
+{
+    let mut candidates = Vec::new();
+    if let Possible { cost, path } = shortest_odd_path(&split, source, target) {
+        candidates.push((cost, path));
+    }
+    Alternatives {
+        split,
+        map,
+        target,
+        found: Vec::new(),
+        cuts: Vec::new(),
+        candidates,
+        last: None,
+        valid_root: Box::new(|_| true),
+    }
+}
+
+pub(crate) type RootFilter<E> = Box<dyn Fn(&[E]) -> bool>;
+
+pub(crate) struct Alternatives<W: Weight, E: Edge<W>, F> {
+    split: UndirectedGraph<W, E>,
+    map: F,
+    target: usize,
+    /// The paths that were returned so far, and their sorted images.
+    found: Vec<Vec<E>>,
+    cuts: Vec<Vec<E>>,
+    candidates: Vec<(W, Vec<E>)>,
+    /// The spurs of the last returned path are only searched when the next one is asked for.
+    last: Option<Vec<E>>,
+    /// Once a root is not valid, no path that starts with it is searched for.
+    valid_root: RootFilter<E>,
+}
+
+impl<W, E, F> Alternatives<W, E, F>
+where
+    W: Weight,
+    E: Edge<W>,
+    F: Fn(&E) -> Option<E>,
+{
+    /// Only the paths whose roots are valid are extended, where a root that extends an invalid root is invalid too.
+    pub(crate) fn pruned(mut self, valid_root: RootFilter<E>) -> Self {
+        self.valid_root = valid_root;
+        self
+    }
+
+    fn cut(&self, path: &[E]) -> Vec<E> {
+        path.iter().flat_map(&self.map).collect()
+    }
+
+    fn add_spurs(&mut self, path: &[E]) {
+        for i in 0..path.len() {
+            let root = &path[..i];
+            if !(self.valid_root)(root) {
+                break;
+            }
+            let deviations: Vec<E> = self
+                .found
+                .iter()
+                .filter(|other| other.len() > i && other[..i] == *root)
+                .map(|other| other[i].clone())
+                .collect();
+            let odd = i % 2 == 0;
+            let spur = path[i].from();
+            if let Some((spur_cost, spur_path)) =
+                spur_path(&self.split, root, spur, self.target, &deviations, odd)
+            {
+                let root_cost: W = root.iter().fold(0.into(), |acc, e| acc + e.weight());
+                let mut next = root.to_vec();
+                next.extend(spur_path);
+                if !self.candidates.iter().any(|(_, other)| *other == next) {
+                    self.candidates.push((root_cost + spur_cost, next));
+                }
+            }
+        }
+    }
+}
+
+impl<W, E, F> Iterator for Alternatives<W, E, F>
+where
+    W: Weight,
+    E: Edge<W>,
+    F: Fn(&E) -> Option<E>,
+{
+    type Item = (W, Vec<E>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(path) = self.last.take() {
+            self.add_spurs(&path);
+        }
+        loop {
+            let best = (0..self.candidates.len()).reduce(|i, j| {
+                if self.candidates[j].0 < self.candidates[i].0 {
+                    j
+                } else {
+                    i
+                }
+            })?;
+            let (cost, path) = self.candidates.remove(best);
+            let cut = self.cut(&path);
+            let mut sorted = cut.clone();
+            sorted.sort();
+            self.found.push(path.clone());
+            // Parallel edges can give different dual paths with the same cut, which is only returned once.
+            if self.cuts.contains(&sorted) {
+                self.add_spurs(&path);
+                continue;
+            }
+            self.cuts.push(sorted);
+            self.last = Some(path);
+            return Some((cost, cut));
+        }
+    }
+}
+
+/// The shortest spur-target-path of the given parity in the split dual, that avoids the vertices of the root
+/// and the edges through which earlier paths with the same root left the spur.
+fn spur_path<W: Weight, E: Edge<W>>(
+    split: &UndirectedGraph<W, E>,
+    root: &[E],
+    spur: usize,
+    target: usize,
+    deviations: &[E],
+    odd: bool,
+) -> Option<(W, Vec<E>)> {
+    let mut blocked = repeat(split.n(), false);
+    for e in root {
+        blocked[e.from()] = true;
+    }
+    let mut graph = UndirectedGraph::new(split.n());
+    for e in split.edges() {
+        if e.from() < e.to()
+            && !blocked[e.from()]
+            && !blocked[e.to()]
+            && !deviations.iter().any(|d| *d == e || d.reverse() == e)
+        {
+            graph.add_edge(e);
+        }
+    }
+
+    let result = if odd {
+        shortest_odd_path(&graph, spur, target)
+    } else {
+        shortest_even_path(&graph, spur, target)
+    };
+    match result {
+        Impossible => None,
+        Possible { cost, path } => Some((cost, path)),
+    }
+}
+
+#[cfg(test)]
+mod test_k_best_diversion {
+    use crate::algorithm::k_best_diversion::k_best_diversions;
+    use crate::algorithm::network_diversion::network_diversion;
+    use crate::algorithm::test_utility::{random_grid_with_edge, sides};
+    use crate::algorithm::verify::verify_diversion;
+    use crate::structure::graph::edge::Edge;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::structure::weight::Weighted;
+    use crate::utility::random::Random;
+
+    /// The costs of all minimal diversion sets, from the sides S of s where both S and its complement are connected.
+    fn all_minimal_costs(
+        planar: &PlanarGraph<u64>,
+        s: usize,
+        t: usize,
+        b: (usize, usize),
+    ) -> Vec<u64> {
+        let n = planar.n();
+        let edges: Vec<_> = planar
+            .real()
+            .edges()
+            .into_iter()
+            .filter(|e| e.from() < e.to())
+            .collect();
+        let connected = |side: &Vec<bool>, start: usize| {
+            let mut seen = vec![false; n];
+            let mut stack = vec![start];
+            seen[start] = true;
+            while let Some(u) = stack.pop() {
+                for e in planar.real().N(u) {
+                    if side[e.to()] == side[start] && !seen[e.to()] {
+                        seen[e.to()] = true;
+                        stack.push(e.to());
+                    }
+                }
+            }
+            (0..n).all(|u| side[u] != side[start] || seen[u])
+        };
+
+        let mut costs = Vec::new();
+        for side in sides(n, s, t) {
+            if side[b.0] == side[b.1] {
+                continue;
+            }
+            if connected(&side, s) && connected(&side, t) {
+                let cost = edges
+                    .iter()
+                    .filter(|e| side[e.from()] != side[e.to()])
+                    .map(|e| e.weight())
+                    .sum::<u64>();
+                let b_weight = planar.real().edge(b.0, b.1).weight();
+                costs.push(cost - b_weight);
+            }
+        }
+        costs.sort();
+        costs
+    }
+
+    #[test]
+    fn test_k_best_example() {
+        let planar: PlanarGraph<f64> = std::fs::read_to_string("data/example.in")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let alternatives = k_best_diversions(&planar, 0, 9, (7, 8), 3).unwrap();
+        assert_eq!(
+            Some(alternatives[0].0),
+            network_diversion(&planar, 0, 9, (7, 8)).map(|(cost, _)| cost)
+        );
+        for (cost, cut) in &alternatives {
+            let pairs: Vec<_> = cut.iter().map(|e| (e.from(), e.to())).collect();
+            let check = verify_diversion(&planar, 0, 9, (7, 8), &pairs).unwrap();
+            assert!(check.is_diversion());
+            assert_eq!(check.cost, *cost);
+        }
+    }
+
+    #[test]
+    fn test_k_best_matches_enumeration() {
+        let mut random = Random::new(13);
+        for _ in 0..20 {
+            let (planar, (u, v)) = random_grid_with_edge::<u64>(&mut random, 4, 3, 9);
+
+            let expected = all_minimal_costs(&planar, 0, 11, (u, v));
+            let found: Vec<u64> = k_best_diversions(&planar, 0, 11, (u, v), 8)
+                .unwrap()
+                .into_iter()
+                .map(|(cost, _)| cost)
+                .collect();
+            assert_eq!(found, expected[..expected.len().min(8)]);
+        }
+    }
+}
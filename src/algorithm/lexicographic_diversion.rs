@@ -0,0 +1,132 @@
+use crate::algorithm::network_diversion::{bfs, DiversionError};
+use crate::algorithm::parity_path::{shortest_path_with_parity, Parity};
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::path_result::PathResult::*;
+use crate::structure::weight::{Lexicographic, Weight};
+use crate::utility::misc::debug;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TieBreak {
+    /// Among the cheapest diversion sets, cut as few edges as possible.
+    FewestEdges,
+    /// Among the cheapest diversion sets, cut edges of the smallest total Euclidean length.
+    ShortestLength,
+}
+
+/**
+Problem: Lexicographic Planar Network Diversion
+In: a planar graph G, two vertices s,t in V(G), a diversion edge b in E(G), and a tie-breaker
+Out: a minimum-cost diversion set X that, among all minimum-cost diversion sets, is smallest by the tie-breaker,
+     or an error if no diversion set exists or the embedding is not planar.
+
+Every dual edge gets the weight (w(e), tie(e)), compared lexicographically, and the odd path is found
+in that dual as usual. The cut is sorted by its endpoints, so the result does not depend on the path direction.
+*/
+pub fn lexicographic_diversion<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+    tie_break: TieBreak,
+) -> Result<(W, Vec<PlanarEdge<W>>), DiversionError> {
+    if !planar.is_planar() {
+        return Err(DiversionError::NotPlanar(planar.genus()));
+    }
+    let points = planar.points();
+    let lift = |e: &PlanarEdge<W>| {
+        let tie = match tie_break {
+            TieBreak::FewestEdges => 1.0,
+            // The dual edge e* crosses the real edge between e*.left and e*.right.
+            TieBreak::ShortestLength => points[e.left()].distance(&points[e.right()]),
+        };
+        e.with_weight(Lexicographic(e.weight, tie))
+    };
+
+    let p = match bfs(planar.real(), s, t, (du, dv)) {
+        Some(p) => p,
+        None => {
+            debug("Could not find any s-t-path that doesn't use the diversion edge, no diversion is needed.".to_string());
+            return Ok((0.into(), Vec::new()));
+        }
+    };
+    let diversion = planar.real().edge(du, dv);
+
+    let mut dual = UndirectedGraph::new(planar.f());
+    for e in planar.dual().edges() {
+        if e.from() < e.to() {
+            dual.add_edge(lift(&e));
+        }
+    }
+    let marked: Vec<_> = p.iter().map(|e| lift(&e.rotate_right())).collect();
+
+    match shortest_path_with_parity(
+        &dual,
+        diversion.left(),
+        diversion.right(),
+        &marked,
+        Parity::Odd,
+    ) {
+        Impossible => {
+            debug(format!(
+                "No diversion set exist, no paths from {} to {} go through ({}, {}).",
+                s, t, du, dv
+            ));
+            Err(DiversionError::NoDiversion)
+        }
+        Possible { cost, path } => {
+            let mut cut: Vec<PlanarEdge<W>> = path
+                .iter()
+                .map(|e| {
+                    let e = e.rotate_right().with_weight(e.weight.0);
+                    if e.from() < e.to() {
+                        e
+                    } else {
+                        e.reverse()
+                    }
+                })
+                .collect();
+            cut.sort();
+            debug(format!(
+                "We have to cut {} edges to divert the network, with a total cost of {} and a tie-breaker of {}.",
+                cut.len(),
+                cost.0,
+                cost.1
+            ));
+            Ok((cost.0, cut))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_lexicographic_diversion {
+    use crate::algorithm::lexicographic_diversion::{lexicographic_diversion, TieBreak};
+    use crate::algorithm::optimal_diversion::optimal_diversions;
+    use crate::algorithm::test_utility::random_grid_with_edge;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_fewest_edges_among_optima() {
+        let mut random = Random::new(17);
+        for _ in 0..40 {
+            let (planar, (u, v)) = random_grid_with_edge::<u64>(&mut random, 5, 4, 3);
+
+            let Ok(all) = optimal_diversions(&planar, 0, 19, (u, v), 1000) else {
+                assert!(
+                    lexicographic_diversion(&planar, 0, 19, (u, v), TieBreak::FewestEdges).is_err()
+                );
+                continue;
+            };
+            assert!(all.complete);
+            let fewest = all.sets.iter().map(Vec::len).min().unwrap();
+            let (cost, cut) =
+                lexicographic_diversion(&planar, 0, 19, (u, v), TieBreak::FewestEdges).unwrap();
+            assert_eq!((cost, cut.len()), (all.cost, fewest));
+            // The same query gives the same cut.
+            let again = lexicographic_diversion(&planar, 0, 19, (u, v), TieBreak::FewestEdges);
+            assert_eq!(again, Ok((cost, cut)));
+        }
+    }
+}
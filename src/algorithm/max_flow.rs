@@ -0,0 +1,169 @@
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::weight::Weight;
+use crate::utility::misc::repeat;
+use queues::{IsQueue, Queue};
+
+/**
+Problem: Minimum S-T-cut
+In: any graph G, two disjoint sets of vertices S,T of V(G), and a capacity for every edge, or None for the edges that are left out
+Out: the value of a maximum S-T-flow, and a minimum S-T-cut of that capacity.
+
+Edmonds-Karp: we augment along shortest paths in the residual graph, where an undirected edge is a pair of opposite arcs
+that are each other's residual. The cut is the boundary of the vertices that S reaches in the final residual graph.
+*/
+pub fn min_cut<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    sources: &[usize],
+    sinks: &[usize],
+    capacity: &dyn Fn(&E) -> Option<W>,
+) -> (W, Vec<E>) {
+    let augmented = augment(graph, sources, sinks, capacity);
+    let seen = &augmented.reached;
+    let cut = augmented
+        .edges
+        .into_iter()
+        .filter(|(e, _)| seen[e.from()] != seen[e.to()])
+        .map(|(e, _)| e)
+        .collect();
+    (augmented.flow, cut)
+}
+
+/**
+Problem: Maximum S-T-flow
+In: the same as `min_cut`
+Out: the value of a maximum S-T-flow, and the flow through every edge that may be used,
+     with the edge in the direction of its flow.
+*/
+pub fn max_flow<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    sources: &[usize],
+    sinks: &[usize],
+    capacity: &dyn Fn(&E) -> Option<W>,
+) -> (W, Vec<(E, W)>) {
+    let augmented = augment(graph, sources, sinks, capacity);
+    let residual = &augmented.residual;
+    // Both arcs start at the capacity, and a flow f from e.from() to e.to() moves f from the first to the second.
+    let flows = augmented
+        .edges
+        .iter()
+        .enumerate()
+        .map(|(i, (e, _))| {
+            let (forward, backward) = (residual[2 * i], residual[2 * i + 1]);
+            if forward <= backward {
+                (e.clone(), (backward - forward) / W::from(2))
+            } else {
+                (e.reverse(), (forward - backward) / W::from(2))
+            }
+        })
+        .collect();
+    (augmented.flow, flows)
+}
+
+struct Augmented<W: Weight, E: Edge<W>> {
+    flow: W,
+    edges: Vec<(E, W)>,
+    /// The arcs 2i and 2i + 1 are the two directions of the i-th edge.
+    residual: Vec<W>,
+    /// The vertices that the sources reach in the final residual graph.
+    reached: Vec<bool>,
+}
+
+fn augment<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    sources: &[usize],
+    sinks: &[usize],
+    capacity: &dyn Fn(&E) -> Option<W>,
+) -> Augmented<W, E> {
+    let edges: Vec<(E, W)> = graph
+        .edges()
+        .into_iter()
+        .filter(|e| e.from() < e.to())
+        .filter_map(|e| capacity(&e).map(|c| (e, c)))
+        .collect();
+    let mut residual: Vec<W> = Vec::new();
+    let mut arcs: Vec<Vec<usize>> = repeat(graph.n(), Vec::new());
+    for (i, (e, c)) in edges.iter().enumerate() {
+        residual.push(*c);
+        residual.push(*c);
+        arcs[e.from()].push(2 * i);
+        arcs[e.to()].push(2 * i + 1);
+    }
+    let head = |arc: usize| {
+        let e = &edges[arc / 2].0;
+        if arc.is_multiple_of(2) {
+            e.to()
+        } else {
+            e.from()
+        }
+    };
+
+    let mut flow: W = 0.into();
+    loop {
+        let mut prev: Vec<Option<usize>> = repeat(graph.n(), None);
+        let mut seen = repeat(graph.n(), false);
+        let mut q: Queue<usize> = Queue::new();
+        for &s in sources {
+            seen[s] = true;
+            q.add(s).unwrap();
+        }
+        while let Ok(u) = q.remove() {
+            for &arc in &arcs[u] {
+                let v = head(arc);
+                if !seen[v] && residual[arc] > 0.into() {
+                    seen[v] = true;
+                    prev[v] = Some(arc);
+                    q.add(v).unwrap();
+                }
+            }
+        }
+
+        let Some(&t) = sinks.iter().find(|&&t| seen[t]) else {
+            return Augmented {
+                flow,
+                edges,
+                residual,
+                reached: seen,
+            };
+        };
+        let mut path = Vec::new();
+        let mut v = t;
+        while let Some(arc) = prev[v] {
+            path.push(arc);
+            v = head(arc ^ 1);
+        }
+        let bottleneck = path
+            .iter()
+            .map(|&arc| residual[arc])
+            .reduce(|a, b| if b < a { b } else { a })
+            .unwrap();
+        for arc in path {
+            residual[arc] = residual[arc] - bottleneck;
+            residual[arc ^ 1] = residual[arc ^ 1] + bottleneck;
+        }
+        flow = flow + bottleneck;
+    }
+}
+
+#[cfg(test)]
+mod test_max_flow {
+    use crate::algorithm::max_flow::min_cut;
+    use crate::structure::graph::edge::{BasicEdge, Edge};
+    use crate::structure::graph::undirected_graph::UndirectedGraph;
+    use crate::structure::weight::Weighted;
+
+    #[test]
+    fn test_min_cut_small() {
+        let graph: UndirectedGraph<u64, BasicEdge<u64>> =
+            "4\n0 1 3\n0 2 2\n1 2 1\n1 3 2\n2 3 3\n".parse().unwrap();
+        let (flow, cut) = min_cut(&graph, &[0], &[3], &|e| Some(e.weight()));
+        assert_eq!(flow, 5);
+        assert_eq!(cut.iter().map(|e| e.weight()).sum::<u64>(), 5);
+
+        // Without the edge 1-3, everything has to go through 2-3, and 0-2 with 1-2 is as cheap.
+        let without = |e: &BasicEdge<u64>| (e.from(), e.to()) != (1, 3);
+        let (flow, cut) = min_cut(&graph, &[0], &[3], &|e| without(e).then(|| e.weight()));
+        assert_eq!(flow, 3);
+        assert_eq!(cut.iter().map(|e| e.weight()).sum::<u64>(), 3);
+    }
+}
@@ -1,5 +1,22 @@
 pub mod bottleneck_path;
+pub mod directed_diversion;
+pub mod exact_diversion;
+pub mod intersection_diversion;
+pub mod k_best_diversion;
+pub mod lexicographic_diversion;
+pub mod max_flow;
+pub mod multi_terminal_diversion;
 pub mod network_diversion;
+pub mod odd_cycle;
 pub mod odd_path;
+pub mod optimal_diversion;
+pub mod pareto_diversion;
+pub mod parity_path;
+pub mod planar_flow;
+pub mod set_diversion;
 pub mod shortest_path;
+#[cfg(test)]
+mod test_utility;
 mod utility;
+pub mod verify;
+pub mod vertex_diversion;
@@ -0,0 +1,427 @@
+use crate::algorithm::directed_diversion::BRANCH_LIMIT;
+use crate::algorithm::parity_path::{shortest_path_with_parity, Parity};
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::path_result::PathResult::*;
+use crate::structure::weight::Weight;
+use crate::utility::misc::{debug, repeat};
+use queues::{IsQueue, Queue};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum MultiTerminalError {
+    /// The sources and the sinks are not two disjoint sets that are not empty.
+    InvalidTerminals,
+    /// No S-T-path uses b, so no diversion set exists.
+    NoDiversion,
+    /// The exact search gave up after this many branches, before it could prove an answer.
+    GaveUp(usize),
+}
+
+impl Display for MultiTerminalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultiTerminalError::InvalidTerminals => write!(
+                f,
+                "The sources and the sinks have to be two disjoint sets that are not empty"
+            ),
+            MultiTerminalError::NoDiversion => write!(f, "No cut found"),
+            MultiTerminalError::GaveUp(limit) => write!(
+                f,
+                "The exact search gave up after {} branches, before it found the cheapest diversion",
+                limit
+            ),
+        }
+    }
+}
+
+/**
+Problem: Multi-terminal Planar Network Diversion
+In: a planar graph G, two disjoint sets of vertices S,T of V(G), and a diversion edge b in E(G)
+Out: a minimum-cost set of edges X, such that every S-T-path in G - X uses b, while some S-T-path in G - X still exists,
+     or why no such set was found.
+
+An optimal X is C - b for a minimal S-T-cut C that contains b. When all of S lie on one face and all of T on one face,
+we add a vertex σ in the first face adjacent to S, and a vertex τ in the second face adjacent to T, with edges that may
+not be cut. Then C is a minimal σ-τ-cut, so we find it as a shortest odd path in the dual of this larger plane graph,
+as in `network_diversion`. If S and T share the only face they lie on, the edges of σ and τ can only be drawn without
+crossings when S and T are two separate blocks around the face. Otherwise, or when the embedding has a higher genus,
+we fall back to `exact_multi_terminal_diversion`, which gives up after `BRANCH_LIMIT` branches.
+*/
+pub fn multi_terminal_diversion<W: Weight>(
+    planar: &PlanarGraph<W>,
+    sources: &[usize],
+    targets: &[usize],
+    (du, dv): (usize, usize),
+) -> Result<(W, Vec<PlanarEdge<W>>), MultiTerminalError> {
+    if sources.is_empty() || targets.is_empty() || sources.iter().any(|s| targets.contains(s)) {
+        return Err(MultiTerminalError::InvalidTerminals);
+    }
+    if !planar.is_planar() {
+        debug(format!(
+            "The embedding has genus {}, so we fall back to the exact search.",
+            planar.genus()
+        ));
+        return exact_multi_terminal_diversion(planar.real(), sources, targets, (du, dv));
+    }
+    let diversion = planar.real().edge(du, dv);
+    let is_diversion =
+        |e: &PlanarEdge<W>| (e.from(), e.to()) == (du, dv) || (e.to(), e.from()) == (du, dv);
+    let p = match terminal_path(planar.real(), sources, targets, &is_diversion) {
+        Some(p) => p,
+        None => {
+            debug("Could not find any S-T-path that doesn't use the diversion edge, no diversion is needed.".to_string());
+            return Ok((0.into(), Vec::new()));
+        }
+    };
+
+    let copies = match face_copies(planar, sources, targets) {
+        Some(copies) => copies,
+        None => {
+            debug(format!(
+                "The {} sources and {} sinks do not lie on faces where they can be joined without crossings. Falling back to the exact search.",
+                sources.len(),
+                targets.len()
+            ));
+            return exact_multi_terminal_diversion(planar.real(), sources, targets, (du, dv));
+        }
+    };
+    let f = copies
+        .values()
+        .copied()
+        .max()
+        .map_or(0, |c| c + 1)
+        .max(planar.f());
+    // The dual edge e* goes from the face on the left of e to the face on its right, or to the copy of that face.
+    let dualize = |e: &PlanarEdge<W>| {
+        let mut d = e.rotate_right();
+        d.from = copies.get(&(e.from(), e.to())).copied().unwrap_or(d.from());
+        d.to = copies.get(&(e.to(), e.from())).copied().unwrap_or(d.to());
+        d
+    };
+    let mut dual = UndirectedGraph::new(f);
+    for e in planar.real().edges() {
+        if e.from() < e.to() {
+            dual.add_edge(dualize(&e));
+        }
+    }
+    // The edges at σ and τ are never crossed, so only the real part of the σ-τ-path counts the crossings.
+    let marked: Vec<PlanarEdge<W>> = p.iter().map(&dualize).collect();
+    let b = dualize(&diversion);
+
+    match shortest_path_with_parity(&dual, b.from(), b.to(), &marked, Parity::Odd) {
+        Impossible => {
+            debug(format!(
+                "No diversion set exist, no paths from S to T go through ({}, {}).",
+                du, dv
+            ));
+            Err(MultiTerminalError::NoDiversion)
+        }
+        Possible { cost, path } => {
+            let cut: Vec<PlanarEdge<W>> = path
+                .iter()
+                .map(|d| {
+                    // The copies changed the faces of d, so the real edge is looked up by its endpoints.
+                    planar.real().edge(d.right(), d.left())
+                })
+                .collect();
+            debug(format!(
+                "We have to cut {} edges to divert the network between {} sources and {} sinks, with a total cost of {}.",
+                cut.len(),
+                sources.len(),
+                targets.len(),
+                cost
+            ));
+            Ok((cost, cut))
+        }
+    }
+}
+
+/// For a face F with S on it and a face with T on it, the face that every directed edge around F lies in
+/// once σ (and τ) is added in F. Edges around F that are not mapped keep F. None if no such faces exist.
+fn face_copies<W: Weight>(
+    planar: &PlanarGraph<W>,
+    sources: &[usize],
+    targets: &[usize],
+) -> Option<HashMap<(usize, usize), usize>> {
+    if planar.n() + planar.f() != planar.m() + 2 {
+        return None;
+    }
+    let faces_around = |terminals: &[usize]| -> Vec<usize> {
+        let mut faces: Vec<usize> = planar
+            .real()
+            .N(terminals[0])
+            .iter()
+            .map(|e| e.left())
+            .collect();
+        for &u in &terminals[1..] {
+            faces.retain(|&f| planar.real().N(u).iter().any(|e| e.left() == f));
+        }
+        faces.dedup();
+        faces
+    };
+
+    let mut next = planar.f();
+    let mut copies = HashMap::new();
+    for fs in faces_around(sources) {
+        for ft in faces_around(targets) {
+            if fs != ft {
+                split_face(planar, fs, &[(sources, 0)], &mut next, &mut copies);
+                split_face(planar, ft, &[(targets, 1)], &mut next, &mut copies);
+                return Some(copies);
+            }
+            if split_face(
+                planar,
+                fs,
+                &[(sources, 0), (targets, 1)],
+                &mut next,
+                &mut copies,
+            ) {
+                return Some(copies);
+            }
+        }
+    }
+    None
+}
+
+/// Cuts the walk around the face at the first visit of every terminal, and gives every arc between two terminals
+/// of the same label its own face, and the arcs between different labels one shared face.
+/// Returns false, without changes, if the labels are not two blocks around the face.
+fn split_face<W: Weight>(
+    planar: &PlanarGraph<W>,
+    face: usize,
+    labelled: &[(&[usize], usize)],
+    next: &mut usize,
+    copies: &mut HashMap<(usize, usize), usize>,
+) -> bool {
+    let walk = planar.face_walk(face);
+    let mut seen = repeat(planar.n(), false);
+    let mut cuts: Vec<(usize, usize)> = Vec::new();
+    for (i, e) in walk.iter().enumerate() {
+        if let Some(&(_, label)) = labelled.iter().find(|(set, _)| set.contains(&e.from())) {
+            if !seen[e.from()] {
+                seen[e.from()] = true;
+                cuts.push((i, label));
+            }
+        }
+    }
+    let changes = (0..cuts.len())
+        .filter(|&j| cuts[j].1 != cuts[(j + 1) % cuts.len()].1)
+        .count();
+    if labelled.len() > 1 && changes != 2 {
+        return false;
+    }
+
+    let mut mixed = None;
+    for j in 0..cuts.len() {
+        let (start, label) = cuts[j];
+        let (end, other) = cuts[(j + 1) % cuts.len()];
+        // The first arc keeps the id of the face.
+        let id = if j == 0 {
+            face
+        } else if label != other && mixed.is_some() {
+            mixed.unwrap()
+        } else {
+            *next += 1;
+            *next - 1
+        };
+        if label != other {
+            mixed.get_or_insert(id);
+        }
+        let mut i = start;
+        loop {
+            copies.insert((walk[i].from(), walk[i].to()), id);
+            i = (i + 1) % walk.len();
+            if i == end {
+                break;
+            }
+        }
+    }
+    true
+}
+
+/**
+Problem: Multi-terminal Network Diversion (exact, exponential)
+In: any graph G, two disjoint sets of vertices S,T of V(G), and a diversion edge b = (du, dv)
+Out: the same as `multi_terminal_diversion`.
+
+Branch and bound: some edge of every S-T-path that avoids b must be cut, so we branch on which edge of such a path is cut,
+and the edges before it on the path are kept in that branch. Cutting more edges never reconnects S and T,
+so branches where S and T are already separated in G - X are pruned. After `BRANCH_LIMIT` branches we give up.
+*/
+pub fn exact_multi_terminal_diversion<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    sources: &[usize],
+    targets: &[usize],
+    (du, dv): (usize, usize),
+) -> Result<(W, Vec<E>), MultiTerminalError> {
+    let b = graph.edge(du, dv);
+    let mut best = None;
+    let mut branches = 0;
+    branch(
+        graph,
+        sources,
+        targets,
+        &b,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        0.into(),
+        &mut best,
+        &mut branches,
+    );
+    if branches > BRANCH_LIMIT {
+        debug(format!(
+            "The exact search gave up after {} branches.",
+            BRANCH_LIMIT
+        ));
+        return Err(MultiTerminalError::GaveUp(BRANCH_LIMIT));
+    }
+    best.ok_or(MultiTerminalError::NoDiversion)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn branch<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    sources: &[usize],
+    targets: &[usize],
+    b: &E,
+    cut: &mut Vec<E>,
+    kept: &mut Vec<(usize, usize)>,
+    cost: W,
+    best: &mut Option<(W, Vec<E>)>,
+    branches: &mut usize,
+) {
+    *branches += 1;
+    if *branches > BRANCH_LIMIT {
+        return;
+    }
+    if best.as_ref().is_some_and(|(c, _)| *c <= cost) {
+        return;
+    }
+    let same = |e: &E, f: &E| {
+        (e.from(), e.to()) == (f.from(), f.to()) || (e.to(), e.from()) == (f.from(), f.to())
+    };
+    let is_cut = |e: &E| cut.iter().any(|c| same(c, e));
+    if terminal_path(graph, sources, targets, &is_cut).is_none() {
+        return;
+    }
+    let path = match terminal_path(graph, sources, targets, &|e| is_cut(e) || same(e, b)) {
+        None => {
+            *best = Some((cost, cut.clone()));
+            return;
+        }
+        Some(path) => path,
+    };
+
+    let before = kept.len();
+    for e in path.iter().rev() {
+        if kept.contains(&(e.from(), e.to())) || kept.contains(&(e.to(), e.from())) {
+            continue;
+        }
+        cut.push(e.clone());
+        branch(
+            graph,
+            sources,
+            targets,
+            b,
+            cut,
+            kept,
+            cost + e.weight(),
+            best,
+            branches,
+        );
+        cut.pop();
+        kept.push((e.from(), e.to()));
+    }
+    kept.truncate(before);
+}
+
+/// The multi-source counterpart of `bfs`: the edges of a path from some vertex of S to some vertex of T
+/// that avoids the removed edges, in order from T back to S.
+fn terminal_path<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    sources: &[usize],
+    targets: &[usize],
+    removed: &dyn Fn(&E) -> bool,
+) -> Option<Vec<E>> {
+    let mut seen = repeat(graph.n(), false);
+    let mut prev: Vec<Option<E>> = repeat(graph.n(), None);
+    let mut q: Queue<usize> = Queue::new();
+    for &s in sources {
+        seen[s] = true;
+        q.add(s).ok()?;
+    }
+
+    while let Ok(u) = q.remove() {
+        if targets.contains(&u) {
+            let mut ret: Vec<E> = Vec::new();
+            let mut curr = u;
+            while let Some(e) = prev[curr].clone() {
+                curr = e.from();
+                ret.push(e);
+            }
+            return Some(ret);
+        }
+        for e in graph.N(u) {
+            if !seen[e.to()] && !removed(e) {
+                seen[e.to()] = true;
+                prev[e.to()] = Some(e.clone());
+                q.add(e.to()).ok()?;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_multi_terminal_diversion {
+    use crate::algorithm::multi_terminal_diversion::{
+        exact_multi_terminal_diversion, multi_terminal_diversion,
+    };
+    use crate::algorithm::network_diversion::network_diversion;
+    use crate::algorithm::test_utility::random_grid_with_edge;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_single_terminals_match_network_diversion() {
+        let planar: PlanarGraph<f64> = std::fs::read_to_string("data/example.in")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            multi_terminal_diversion(&planar, &[0], &[9], (7, 8))
+                .ok()
+                .map(|(cost, _)| cost),
+            network_diversion(&planar, 0, 9, (7, 8)).map(|(cost, _)| cost)
+        );
+    }
+
+    #[test]
+    fn test_planar_matches_exact() {
+        let mut random = Random::new(23);
+        // Sides of the grid, two separate blocks around the outer face, an inner face and vertices on no common face.
+        let terminals: [(&[usize], &[usize]); 6] = [
+            (&[0, 4, 8], &[3, 7, 11]),
+            (&[0, 1], &[11]),
+            (&[0, 4], &[8, 9]),
+            (&[5, 6], &[3, 11]),
+            (&[0, 6], &[3, 9]),
+            (&[0, 11], &[3, 8]),
+        ];
+        for _ in 0..20 {
+            let (planar, (u, v)) = random_grid_with_edge::<u64>(&mut random, 4, 3, 9);
+            for (sources, targets) in terminals {
+                let planar_result =
+                    multi_terminal_diversion(&planar, sources, targets, (u, v)).map(|(c, _)| c);
+                let exact_result =
+                    exact_multi_terminal_diversion(planar.real(), sources, targets, (u, v))
+                        .map(|(c, _)| c);
+                assert_eq!(planar_result, exact_result);
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
-use crate::algorithm::odd_path::shortest_odd_path;
-use crate::algorithm::utility::split_edges;
+use crate::algorithm::bottleneck_path::shortest_bottleneck_path;
+use crate::algorithm::exact_diversion::{exact_network_diversion, ExactDiversionError};
+use crate::algorithm::parity_path::{shortest_path_with_parity, Parity};
 use crate::structure::graph::edge::Edge;
 use crate::structure::graph::planar_edge::PlanarEdge;
 use crate::structure::graph::planar_graph::PlanarGraph;
@@ -8,6 +9,52 @@ use crate::structure::path_result::PathResult::*;
 use crate::structure::weight::Weight;
 use crate::utility::misc::{debug, repeat};
 use queues::{IsQueue, Queue};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum ProtectedError {
+    /// No s-t-path goes through b, so no diversion set exists at all.
+    NoDiversion,
+    /// Every diversion set cuts a protected edge.
+    Protected,
+    /// The exact search off the plane gave up after this many branches.
+    GaveUp(usize),
+}
+
+impl Display for ProtectedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtectedError::NoDiversion => write!(f, "No cut found, no s-t-path uses b"),
+            ProtectedError::Protected => {
+                write!(f, "No cut found that avoids the protected edges")
+            }
+            ProtectedError::GaveUp(limit) => {
+                write!(f, "{}", ExactDiversionError::GaveUp(*limit))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DiversionError {
+    /// No s-t-path goes through b, so no diversion set exists at all.
+    NoDiversion,
+    /// The embedding has this genus, and on a surface other than the plane, a cycle in the dual need not be a cut.
+    NotPlanar(usize),
+}
+
+impl Display for DiversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiversionError::NoDiversion => write!(f, "No cut found"),
+            DiversionError::NotPlanar(genus) => write!(
+                f,
+                "The embedding has genus {}, but this needs a plane embedding",
+                genus
+            ),
+        }
+    }
+}
 
 pub fn network_diversion<W: Weight>(
     planar: &PlanarGraph<W>,
@@ -15,47 +62,139 @@ pub fn network_diversion<W: Weight>(
     t: usize,
     (du, dv): (usize, usize),
 ) -> Option<(W, Vec<PlanarEdge<W>>)> {
+    protected_diversion(planar, s, t, (du, dv), &[]).ok()
+}
+
+/**
+Problem: Planar Network Diversion with protected edges
+In: a planar graph G, two vertices s,t in V(G), a diversion edge b in E(G), and a set of protected edges F of E(G)
+Out: a minimum-cost diversion set X with no edges of F, or whether no diversion set exists at all, or every one
+     needs a protected edge.
+
+The dual edges of F are left out of the dual, so the odd path can never cross a protected edge.
+On a surface of higher genus, a cycle in the dual need not separate s from t, so we fall back to
+`exact_network_diversion`, which keeps the two ends of a protected edge on the same side, and may give up.
+*/
+pub fn protected_diversion<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+    protected: &[(usize, usize)],
+) -> Result<(W, Vec<PlanarEdge<W>>), ProtectedError> {
+    if !planar.is_planar() {
+        debug(format!(
+            "The embedding has genus {}, so we fall back to the exact search.",
+            planar.genus()
+        ));
+        return exact_protected_diversion(planar, s, t, (du, dv), protected);
+    }
     if let Some(p) = bfs(planar.real(), s, t, (du, dv)) {
-        let path = p.iter().map(|e| e.rotate_right()).collect();
-        let diversion = planar
-            .real()
-            .N(du)
-            .iter()
-            .find(|l| l.to() == dv)
-            .expect("The diversion edge doesn't exist")
-            .clone();
-        let (split, map) = split_edges(planar.dual(), path);
-        match shortest_odd_path(&split, diversion.left(), diversion.right()) {
+        let path: Vec<PlanarEdge<W>> = p.iter().map(|e| e.rotate_right()).collect();
+        let diversion = planar.real().edge(du, dv);
+        let without;
+        let dual = if protected.is_empty() {
+            planar.dual()
+        } else {
+            without = without_protected(planar, protected);
+            &without
+        };
+        match shortest_path_with_parity(
+            dual,
+            diversion.left(),
+            diversion.right(),
+            &path,
+            Parity::Odd,
+        ) {
+            Impossible
+                if !protected.is_empty()
+                    && matches!(
+                        shortest_path_with_parity(
+                            planar.dual(),
+                            diversion.left(),
+                            diversion.right(),
+                            &path,
+                            Parity::Odd,
+                        ),
+                        Possible { .. }
+                    ) =>
+            {
+                debug("No diversion set exist without cutting a protected edge.".to_string());
+                Err(ProtectedError::Protected)
+            }
             Impossible => {
                 debug(format!(
                     "No diversion set exist, no paths from {} to {} go through ({}, {}).",
                     s, t, du, dv
                 ));
-                None
+                Err(ProtectedError::NoDiversion)
             }
-            Possible { cost, path } => {
-                let mapped: Vec<PlanarEdge<W>> = path.iter().flat_map(|e| map(e)).collect();
+            Possible { cost, path: mapped } => {
                 let rotated: Vec<PlanarEdge<W>> = mapped.iter().map(|e| e.rotate_right()).collect();
                 debug(format!(
                     "We have to cut {} edges to divert the network, with a total cost of {}.",
-                    path.len(),
+                    mapped.len(),
                     cost
                 ));
-                if path.len() < 15 {
+                if mapped.len() < 15 {
                     debug(format!("Dual diversion set: {:?}", mapped));
                     debug(format!("Real diversion set: {:?}\n", rotated));
                 }
 
-                Some((cost, rotated))
+                Ok((cost, rotated))
             }
         }
     } else {
         debug(format!("Could not find any s-t-path that doesn't use the diversion edge, no diversion is needed."));
-        Some((0.into(), Vec::new()))
+        Ok((0.into(), Vec::new()))
     }
 }
 
-fn bfs<W: Weight, E: Edge<W>>(
+fn exact_protected_diversion<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    b: (usize, usize),
+    protected: &[(usize, usize)],
+) -> Result<(W, Vec<PlanarEdge<W>>), ProtectedError> {
+    let is_protected = |e: &PlanarEdge<W>| {
+        protected.contains(&(e.from(), e.to())) || protected.contains(&(e.to(), e.from()))
+    };
+    match exact_network_diversion(planar.real(), s, t, b, &is_protected) {
+        Ok(found) => Ok(found),
+        Err(ExactDiversionError::GaveUp(limit)) => Err(ProtectedError::GaveUp(limit)),
+        // Without protected edges, every simple s-t-path through b is left by cutting all the other edges.
+        Err(ExactDiversionError::NoDiversion) => {
+            match shortest_bottleneck_path(planar.real(), s, t, b) {
+                Possible { .. } if !protected.is_empty() => {
+                    debug("No diversion set exist without cutting a protected edge.".to_string());
+                    Err(ProtectedError::Protected)
+                }
+                _ => Err(ProtectedError::NoDiversion),
+            }
+        }
+    }
+}
+
+/// The dual of G without the dual edges of the protected edges.
+fn without_protected<W: Weight>(
+    planar: &PlanarGraph<W>,
+    protected: &[(usize, usize)],
+) -> UndirectedGraph<W, PlanarEdge<W>> {
+    // A dual edge e* separates the faces on each side of e, so its real endpoints are (e*.right, e*.left).
+    let is_protected = |e: &PlanarEdge<W>| {
+        protected.contains(&(e.left(), e.right())) || protected.contains(&(e.right(), e.left()))
+    };
+    let mut dual = UndirectedGraph::new(planar.f());
+    for e in planar.dual().edges() {
+        if e.from() < e.to() && !is_protected(&e) {
+            dual.add_edge(e);
+        }
+    }
+    dual
+}
+
+pub(crate) fn bfs<W: Weight, E: Edge<W>>(
     graph: &UndirectedGraph<W, E>,
     s: usize,
     t: usize,
@@ -139,3 +278,149 @@ mod visualize_diversions {
         visualize("data/delaunay_graphs/planar_delaunay_graphs", "delaunay35");
     }
 }
+
+#[cfg(test)]
+mod test_protected_diversion {
+    use crate::algorithm::network_diversion::{
+        network_diversion, protected_diversion, ProtectedError,
+    };
+    use crate::algorithm::test_utility::random_grid;
+    use crate::algorithm::verify::verify_diversion;
+    use crate::structure::graph::edge::Edge;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::utility::generator::grid;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_protected_example() {
+        let planar: PlanarGraph<f64> = std::fs::read_to_string("data/example.in")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let b = (7, 8);
+        let (cost, _) = protected_diversion(&planar, 0, 9, b, &[(0, 1), (8, 6)]).unwrap();
+        assert_eq!(cost, 3.0);
+        // The unique diversion set needs 3--4.
+        assert_eq!(
+            protected_diversion(&planar, 0, 9, b, &[(4, 3)]).err(),
+            Some(ProtectedError::Protected)
+        );
+        // No simple path from 0 to 1 goes through 1--2 of this path.
+        let path: PlanarGraph<f64> = grid(3, 1).to_string().parse().unwrap();
+        assert_eq!(
+            protected_diversion(&path, 0, 1, (1, 2), &[(0, 1)]).err(),
+            Some(ProtectedError::NoDiversion)
+        );
+    }
+
+    #[test]
+    fn test_protected_grid() {
+        let mut random = Random::new(11);
+        let planar: PlanarGraph<u64> = random_grid(&mut random, 5, 5, 9);
+        let b = (12, 13);
+
+        let (cost, cut) = network_diversion(&planar, 0, 24, b).unwrap();
+        let protected = [(cut[0].from(), cut[0].to())];
+        let (protected_cost, protected_cut) =
+            protected_diversion(&planar, 0, 24, b, &protected).unwrap();
+        assert!(protected_cost >= cost);
+
+        let pairs: Vec<_> = protected_cut.iter().map(|e| (e.from(), e.to())).collect();
+        assert!(!pairs.contains(&protected[0]));
+        assert!(!pairs.contains(&(protected[0].1, protected[0].0)));
+        let check = verify_diversion(&planar, 0, 24, b, &pairs).unwrap();
+        assert!(check.is_diversion());
+        assert_eq!(check.cost, protected_cost);
+    }
+}
+
+#[cfg(test)]
+mod test_higher_genus {
+    use crate::algorithm::intersection_diversion::intersection_diversion;
+    use crate::algorithm::k_best_diversion::k_best_diversions;
+    use crate::algorithm::lexicographic_diversion::{lexicographic_diversion, TieBreak};
+    use crate::algorithm::network_diversion::{
+        network_diversion, protected_diversion, DiversionError,
+    };
+    use crate::algorithm::optimal_diversion::optimal_diversions;
+    use crate::algorithm::pareto_diversion::{fewest_closures, pareto_diversions};
+    use crate::algorithm::set_diversion::exact_set_diversion;
+    use crate::algorithm::vertex_diversion::{vertex_diversion, VertexDiversionError};
+    use crate::structure::graph::edge::Edge;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::utility::generator::torus;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_torus_matches_all_cuts() {
+        let mut random = Random::new(43);
+        for _ in 0..10 {
+            let (points, mut rotations) = torus(4, 3);
+            for around in rotations.iter_mut() {
+                for (_, w) in around.iter_mut() {
+                    *w = (1 + random.below(9)) as f64;
+                }
+            }
+            let planar = PlanarGraph::from_rotations(points, rotations).unwrap();
+            let edges: Vec<_> = planar
+                .real()
+                .edges()
+                .into_iter()
+                .filter(|e| e.from() < e.to())
+                .collect();
+            let b = edges[random.below(edges.len())].clone();
+
+            let (cost, cut) = network_diversion(&planar, 0, 7, (b.from(), b.to())).unwrap();
+            let all_cuts =
+                exact_set_diversion(planar.real(), 0, 7, std::slice::from_ref(&b)).unwrap();
+            assert_eq!(cost, all_cuts.0);
+
+            if let Some(e) = cut.first() {
+                let protected = [(e.from(), e.to())];
+                if let Ok((protected_cost, protected_cut)) =
+                    protected_diversion(&planar, 0, 7, (b.from(), b.to()), &protected)
+                {
+                    assert!(protected_cost >= cost);
+                    assert!(protected_cut
+                        .iter()
+                        .all(|c| (c.from(), c.to()) != (e.from(), e.to())
+                            && (c.to(), c.from()) != (e.from(), e.to())));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_plane_only_algorithms_report_the_genus() {
+        let (points, rotations) = torus(4, 3);
+        let planar: PlanarGraph<f64> = PlanarGraph::from_rotations(points, rotations).unwrap();
+        assert_eq!(
+            k_best_diversions(&planar, 0, 7, (0, 1), 3),
+            Err(DiversionError::NotPlanar(1))
+        );
+        assert_eq!(
+            intersection_diversion(&planar, 0, 7, (0, 1), &[None; 12]).map(|_| ()),
+            Err(DiversionError::NotPlanar(1))
+        );
+        assert_eq!(
+            fewest_closures(&planar, 0, 7, (0, 1)).map(|_| ()),
+            Err(DiversionError::NotPlanar(1))
+        );
+        assert_eq!(
+            pareto_diversions(&planar, 0, 7, (0, 1), 10).map(|_| ()),
+            Err(DiversionError::NotPlanar(1))
+        );
+        assert_eq!(
+            optimal_diversions(&planar, 0, 7, (0, 1), 10).map(|_| ()),
+            Err(DiversionError::NotPlanar(1))
+        );
+        assert_eq!(
+            lexicographic_diversion(&planar, 0, 7, (0, 1), TieBreak::FewestEdges).map(|_| ()),
+            Err(DiversionError::NotPlanar(1))
+        );
+        assert_eq!(
+            vertex_diversion(&planar, 0, 7, 1).map(|_| ()),
+            Err(VertexDiversionError::NotPlanar(1))
+        );
+    }
+}
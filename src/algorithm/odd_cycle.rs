@@ -0,0 +1,186 @@
+use crate::algorithm::odd_path::shortest_odd_path;
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::path_result::{PathResult, PathResult::*};
+use crate::structure::weight::Weight;
+use crate::utility::misc::repeat;
+
+/**
+Problem: Shortest Odd Cycle through a vertex
+In: an undirected graph G, and a vertex v in V(G)
+Out: the shortest simple cycle through v in G that uses an odd number of edges, as its edges from v back to v,
+     if one exists.
+
+We split v in two: a copy v' gets a copy of every edge at v. An odd v-v'-path visits every other vertex at most once,
+and it can't use the two copies of the same edge, since that path v-x-v' has two edges. So it is an odd cycle through v,
+and every odd cycle through v with at least three edges is such a path. A loop at v is an odd cycle of one edge.
+*/
+pub fn shortest_odd_cycle_through<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    v: usize,
+) -> PathResult<W, E> {
+    let copy = graph.n();
+    let mut split = UndirectedGraph::new(copy + 1);
+    let mut copies = Vec::new();
+    for e in graph.edges() {
+        if e.from() < e.to() {
+            split.add_edge(e.clone());
+        }
+        if e.to() == v && e.from() != v {
+            // The edge (x, v) becomes (x, v'), with the same weight.
+            let (to_copy, _) = e.subdivide(copy);
+            split.add_edge(to_copy.clone());
+            copies.push((to_copy, e));
+        }
+    }
+
+    let loop_at_v = graph
+        .N(v)
+        .iter()
+        .filter(|e| e.to() == v)
+        .min_by(|a, b| a.weight().partial_cmp(&b.weight()).unwrap())
+        .cloned();
+    let cycle = match shortest_odd_path(&split, v, copy) {
+        Impossible => None,
+        Possible { cost, mut path } => {
+            let last = path.pop().unwrap();
+            let (_, original) = copies.iter().find(|(c, _)| *c == last).unwrap();
+            path.push(original.clone());
+            Some((cost, path))
+        }
+    };
+    match (cycle, loop_at_v) {
+        (Some((cost, path)), Some(e)) if cost <= e.weight() => Possible { cost, path },
+        (_, Some(e)) => Possible {
+            cost: e.weight(),
+            path: vec![e],
+        },
+        (Some((cost, path)), None) => Possible { cost, path },
+        (None, None) => Impossible,
+    }
+}
+
+/**
+Problem: Shortest Odd Cycle
+In: an undirected graph G
+Out: the shortest simple cycle in G that uses an odd number of edges, if one exists, so if G is not bipartite.
+
+A bipartite graph has no odd cycles at all, which a 2-colouring finds in linear time. Otherwise we look for the shortest
+odd cycle through every vertex in turn, after which the vertex is removed, since the later cycles can avoid it.
+*/
+pub fn shortest_odd_cycle<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+) -> PathResult<W, E> {
+    if is_bipartite(graph) {
+        return Impossible;
+    }
+    let mut remaining = graph.clone();
+    let mut best: PathResult<W, E> = Impossible;
+    for v in graph.vertices() {
+        if let Possible { cost, path } = shortest_odd_cycle_through(&remaining, v) {
+            if !matches!(best, Possible { cost: c, .. } if c <= cost) {
+                best = Possible { cost, path };
+            }
+        }
+        let at_v = remaining.N(v).clone();
+        remaining.delete_edges(&at_v);
+    }
+    best
+}
+
+fn is_bipartite<W: Weight, E: Edge<W>>(graph: &UndirectedGraph<W, E>) -> bool {
+    let mut colour: Vec<Option<bool>> = repeat(graph.n(), None);
+    for start in graph.vertices() {
+        if colour[start].is_some() {
+            continue;
+        }
+        colour[start] = Some(false);
+        let mut stack = vec![start];
+        while let Some(u) = stack.pop() {
+            for e in graph.N(u) {
+                let other = colour[u].map(|c| !c);
+                match colour[e.to()] {
+                    None => {
+                        colour[e.to()] = other;
+                        stack.push(e.to());
+                    }
+                    Some(c) if Some(c) != other => return false,
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod test_odd_cycle {
+    use crate::algorithm::odd_cycle::{shortest_odd_cycle, shortest_odd_cycle_through};
+    use crate::algorithm::test_utility::{brute_force, random_graph};
+    use crate::structure::graph::edge::{BasicEdge, Edge};
+    use crate::structure::graph::undirected_graph::UndirectedGraph;
+    use crate::structure::path_result::PathResult::*;
+    use crate::structure::weight::Weighted;
+    use crate::utility::random::Random;
+
+    /// The cheapest simple odd cycle through v: a loop at v, or an even simple path from v to a neighbour w
+    /// closed by an edge (w, v).
+    fn cheapest_odd_cycle(graph: &UndirectedGraph<u64, BasicEdge<u64>>, v: usize) -> Option<u64> {
+        graph
+            .N(v)
+            .iter()
+            .filter_map(|e| {
+                if e.to() == v {
+                    Some(e.weight())
+                } else {
+                    brute_force(graph, v, e.to(), &|path| path.len().is_multiple_of(2))
+                        .map(|cost| cost + e.weight())
+                }
+            })
+            .min()
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut random = Random::new(44);
+        for _ in 0..30 {
+            let graph = random_graph(&mut random);
+            let mut global: Option<u64> = None;
+            for v in 0..7 {
+                let expected = cheapest_odd_cycle(&graph, v);
+                match shortest_odd_cycle_through(&graph, v) {
+                    Possible { cost, path } => {
+                        assert_eq!(Some(cost), expected);
+                        assert_eq!(path.len() % 2, 1);
+                        assert_eq!((path[0].from(), path.last().unwrap().to()), (v, v));
+                        assert!(path.windows(2).all(|w| w[0].to() == w[1].from()));
+                    }
+                    Impossible => assert_eq!(expected, None),
+                }
+                global = match (global, expected) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            match shortest_odd_cycle(&graph) {
+                Possible { cost, .. } => assert_eq!(Some(cost), global),
+                Impossible => assert_eq!(global, None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bipartite_and_loops() {
+        let mut square = UndirectedGraph::new(4);
+        for (u, v) in [(0, 1), (1, 2), (2, 3), (3, 0)] {
+            square.add_edge(BasicEdge::new(u, v, 1u64));
+        }
+        assert!(matches!(shortest_odd_cycle(&square), Impossible));
+        square.add_edge(BasicEdge::new(2, 2, 7));
+        assert!(matches!(
+            shortest_odd_cycle(&square),
+            Possible { cost: 7, .. }
+        ));
+        assert!(matches!(shortest_odd_cycle_through(&square, 0), Impossible));
+    }
+}
@@ -15,6 +15,7 @@ pub struct DerigsAlgorithm<W: Weight, E: Edge<W>> {
     d_plus: Vec<Cost<W>>,
     d_minus: Vec<Cost<W>>,
     pred: Vec<Option<E>>,
+    bridge: Vec<Option<E>>,
     basis: UnionFindBase,
     s: usize,
     t: usize,
@@ -38,7 +39,7 @@ fn is_unique<W: Weight, E: Edge<W>>(vec: &Vec<E>) -> bool {
 fn is_walk<W: Weight, E: Edge<W>>(_graph: &UndirectedGraph<W, E>, path: &Vec<E>) -> bool {
     for i in 1..path.len() {
         if path[i - 1].to() != path[i].from() {
-            println!(
+            eprintln!(
                 "Not same endpoint: ({}, {}))",
                 path[i - 1].to(),
                 path[i].from()
@@ -53,6 +54,8 @@ fn is_walk<W: Weight, E: Edge<W>>(_graph: &UndirectedGraph<W, E>, path: &Vec<E>)
 Problem: Shortest Odd Path
 In: an undirected graph G, two vertices s,t in V(G)
 Out: the shortest s-t-path in G that uses an odd number of edges, if one exists.
+
+When s = t, the only s-t-path is the empty one, which is even, so there is no odd path.
 */
 
 pub fn shortest_odd_path<W: Weight, E: Edge<W>>(
@@ -63,6 +66,115 @@ pub fn shortest_odd_path<W: Weight, E: Edge<W>>(
     DerigsAlgorithm::init(graph, s, t).solve()
 }
 
+/**
+Problem: Shortest Even Path
+In: an undirected graph G, two vertices s,t in V(G)
+Out: the shortest s-t-path in G that uses an even number of edges, if one exists.
+
+One more edge of weight 0, from t to a new pendant vertex, flips the parity: an odd path from s to the pendant
+is an even s-t-path followed by that edge, since the pendant can only be reached through t.
+When s = t, the empty path is the shortest even path, with cost 0.
+*/
+pub fn shortest_even_path<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+) -> PathResult<W, E> {
+    if s == t {
+        return Possible {
+            cost: 0.into(),
+            path: Vec::new(),
+        };
+    }
+    let pendant = graph.n();
+    let zero = match graph.N(t).first() {
+        Some(e) => e.reverse().subdivide(pendant).1,
+        None => return Impossible,
+    };
+    let mut extended = UndirectedGraph::new(pendant + 1);
+    for e in graph.edges() {
+        if e.from() < e.to() {
+            extended.add_edge(e);
+        }
+    }
+    extended.add_edge(zero);
+
+    match shortest_odd_path(&extended, s, pendant) {
+        Impossible => Impossible,
+        Possible { cost, mut path } => {
+            path.pop();
+            Possible { cost, path }
+        }
+    }
+}
+
+/**
+Problem: Shortest Odd and Even Paths from one source
+In: an undirected graph G, and a vertex s in V(G)
+Out: for every vertex v, the costs and paths of the shortest odd and the shortest even s-v-path in G, if they exist.
+
+The search runs in a mirror graph where only s has no mirror, until no vertex is left to scan. An odd alternating path
+to the original v and to its mirror v' is an odd and an even s-v-path in G, and its cost is the even label
+of the mirror of its last vertex, which is set when that vertex is scanned or when it becomes even in a blossom.
+*/
+pub fn parity_distances<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    s: usize,
+) -> ParityDistances<W, E> {
+    let mut search = DerigsAlgorithm::init(graph, s, s);
+    search.scan(s);
+    while !search.control() {}
+    ParityDistances { search }
+}
+
+/// The labels of a completed search from s, see `parity_distances`.
+pub struct ParityDistances<W: Weight, E: Edge<W>> {
+    search: DerigsAlgorithm<W, E>,
+}
+
+impl<W: Weight, E: Edge<W>> ParityDistances<W, E> {
+    pub fn odd(&self, v: usize) -> Option<W> {
+        self.label(v + self.search.orig_n)
+    }
+    pub fn even(&self, v: usize) -> Option<W> {
+        self.label(v)
+    }
+    pub fn odd_path(&self, v: usize) -> PathResult<W, E> {
+        self.path_to(v, v + self.search.orig_n)
+    }
+    pub fn even_path(&self, v: usize) -> PathResult<W, E> {
+        self.path_to(v, v)
+    }
+
+    /// The even label of u, where the alternating path to the mirror of u ends.
+    fn label(&self, u: usize) -> Option<W> {
+        match self.search.d_plus[u] {
+            Finite(cost) if self.search.completed[u] => Some(cost),
+            _ => None,
+        }
+    }
+
+    fn path_to(&self, v: usize, u: usize) -> PathResult<W, E> {
+        let cost = match self.label(u) {
+            Some(cost) => cost,
+            None => return Impossible,
+        };
+        if v == self.search.s {
+            return Possible {
+                cost,
+                path: Vec::new(),
+            };
+        }
+        let path = self
+            .search
+            .path(u, self.search.s)
+            .into_iter()
+            .map(|e| self.search.unmirror(e))
+            .collect();
+        Possible { cost, path }
+    }
+}
+
 fn is_simple<W: Weight, E: Edge<W>>(graph: &UndirectedGraph<W, E>, path: &Vec<E>) -> bool {
     return is_unique(&path) && is_walk(&graph, &path);
 }
@@ -81,31 +193,22 @@ impl<W: Weight, E: Edge<W>> DerigsAlgorithm<W, E> {
         ));
 
         let mut d_plus = repeat(n, Infinite);
-        let mut d_minus = repeat(n, Infinite);
-        let mut pred = repeat(n, None);
         let mut completed = repeat(n, false);
-        let mut pq = BinaryHeap::new();
         d_plus[s] = Finite(0.into());
-
-        for e in &mirror_graph[&s] {
-            pq.push(Reverse(Vertex(e.weight(), e.to())));
-            d_minus[e.to()] = Finite(e.weight());
-            pred[e.to()] = Some(e.clone());
-        }
-        completed[s] = true;
         completed[s + graph.n()] = true;
 
         DerigsAlgorithm {
             graph: mirror_graph,
             d_plus,
-            d_minus,
-            pred,
+            d_minus: repeat(n, Infinite),
+            pred: repeat(n, None),
+            bridge: repeat(n, None),
             basis: UnionFindBase::new(n),
             s,
             t,
             orig_n: graph.n(),
             completed,
-            pq,
+            pq: BinaryHeap::new(),
             in_current_blossom: repeat(n, false),
         }
     }
@@ -115,6 +218,7 @@ impl<W: Weight, E: Edge<W>> DerigsAlgorithm<W, E> {
             return Impossible;
         }
 
+        self.scan(self.s);
         while !self.control() {}
 
         if self.d_minus[self.t].is_infinite() {
@@ -125,19 +229,11 @@ impl<W: Weight, E: Edge<W>> DerigsAlgorithm<W, E> {
             return Impossible;
         }
 
-        let mut curr = self.pred[self.t].clone().unwrap();
-        let mut cost = curr.weight();
-        let mut path = vec![curr.clone()];
-        while curr.from() != self.s {
-            curr = self.pred[self.mirror(curr.from())].clone().unwrap();
-            cost = cost + curr.weight();
-            if curr.from() < self.orig_n {
-                path.push(curr.clone());
-            } else {
-                path.push(curr.shift_by(-(self.orig_n as i64)))
-            }
-        }
-        path.reverse();
+        let last = self.pred[self.t].clone().unwrap();
+        let mut path = self.path(last.from(), self.s);
+        path.push(last);
+        let path: Vec<E> = path.into_iter().map(|e| self.unmirror(e)).collect();
+        let cost = path.iter().fold(0.into(), |acc, e| acc + e.weight());
         debug(format!("Path of cost {} is possible: {:?\n\n}", cost, path));
         assert!(is_simple(&self.graph, &path));
         Possible { cost, path }
@@ -147,7 +243,8 @@ impl<W: Weight, E: Edge<W>> DerigsAlgorithm<W, E> {
         while let Some(Reverse(todo)) = self.pq.peek() {
             match todo {
                 Vertex(_, u) => {
-                    if self.completed[*u] {
+                    // Either u is already even, or it has been reached before and its mirror is even.
+                    if self.completed[*u] || self.completed[self.mirror(*u)] {
                         self.pq.pop();
                     } else {
                         break;
@@ -173,8 +270,8 @@ impl<W: Weight, E: Edge<W>> DerigsAlgorithm<W, E> {
                 self.d_plus[m] = self.d_minus[u];
                 self.scan(m);
             }
-            Some(Reverse(Blossom(_, e))) => {
-                self.blossom(&e);
+            Some(Reverse(Blossom(delta, e))) => {
+                self.blossom(&e, delta);
             }
         }
 
@@ -194,137 +291,129 @@ impl<W: Weight, E: Edge<W>> DerigsAlgorithm<W, E> {
             let w = e.weight();
             let v = e.to();
             let new_dist_v = dist_u + w;
-            if !self.completed[v] {
-                if Finite(new_dist_v) >= self.d_minus[v] {
-                    continue;
+            if self.completed[v] {
+                if let (Finite(dist_v), false) = (self.d_plus[v], self.basis.same_base(u, v)) {
+                    self.pq
+                        .push(Reverse(Blossom(dist_u + dist_v + w, e.clone())));
                 }
-
+            } else if !self.completed[self.mirror(v)] && Finite(new_dist_v) < self.d_minus[v] {
                 self.d_minus[v] = Finite(new_dist_v);
                 self.pred[v] = Some(e.clone());
                 self.pq.push(Reverse(Vertex(new_dist_v, v)));
-            } else if let (Finite(dist_v), true) = (self.d_plus[v], !self.basis.same_base(u, v)) {
-                self.pq
-                    .push(Reverse(Blossom(dist_u + dist_v + w, e.clone())));
-                if Finite(new_dist_v) < self.d_minus[v] {
-                    self.d_minus[v] = Finite(new_dist_v);
-                    self.pred[v] = Some(e.clone());
-                }
             }
         }
     }
 
-    fn blossom(&mut self, e: &E) {
-        let (b, p1, p2) = self.backtrack_blossom(e);
+    /**
+    The edge e closes an odd cycle of even vertices. Every odd vertex on the cycle becomes even,
+    and remembers e so that we can walk around the blossom when we reconstruct the path.
+    */
+    fn blossom(&mut self, e: &E, delta: W) {
+        let b = self.common_base(e.from(), e.to());
 
-        let s1 = self.set_blossom_values(&p1);
-        let s2 = self.set_blossom_values(&p2);
+        let mut evens = self.shrink(e, b, delta);
+        evens.extend(self.shrink(&e.reverse(), b, delta));
 
-        self.set_edge_bases(b, &p1);
-        self.set_edge_bases(b, &p2);
-
-        for u in s1 {
+        for u in evens {
             self.scan(u);
         }
-        for v in s2 {
-            self.scan(v);
+    }
+
+    // The base of the blossom below an even base u, or None when u = s.
+    fn parent_base(&self, u: usize) -> Option<usize> {
+        if u == self.s {
+            return None;
         }
+        let e = self.pred[self.mirror(u)].as_ref().unwrap_or_else(|| {
+            panic!(
+                "    Tried to unwrap pred[{}], but it's not defined!",
+                self.mirror(u)
+            )
+        });
+        Some(self.basis[e.from()])
     }
 
-    fn backtrack_blossom(&mut self, e: &E) -> (usize, Vec<E>, Vec<E>) {
-        let mut p1: Vec<E> = vec![e.reverse()];
-        let mut p2: Vec<E> = vec![e.clone()];
-
-        let mut u = self.basis[e.to()];
-        let mut v = self.basis[e.from()];
-
-        self.in_current_blossom[u] = true;
-        self.in_current_blossom[v] = true;
-
-        loop {
-            if u != self.s {
-                u = self.basis[self.mirror(u)];
-                self.in_current_blossom[u] = true;
-                let e = self.pred[u].clone().expect(
-                    format!("    Tried to unwrap pred[{}], but it's not defined!", u).as_str(),
-                );
-                u = self.basis[e.from()];
-                p1.push(e);
-
-                if self.in_current_blossom[u] {
-                    p1.pop();
-                    self.in_current_blossom[u] = false;
-                    while let Some(e) = p2.last() {
-                        let vv = self.basis[e.from()];
-                        self.in_current_blossom[vv] = false;
-                        p2.pop();
-                        if vv == u {
-                            break;
-                        }
-                    }
-                    return (u, p1, p2);
+    // The first base that the paths from the blossoms of u and v back to s have in common.
+    fn common_base(&mut self, u: usize, v: usize) -> usize {
+        let (mut a, mut b) = (Some(self.basis[u]), Some(self.basis[v]));
+        let base = loop {
+            if let Some(x) = a {
+                if self.in_current_blossom[x] {
+                    break x;
                 }
-                self.in_current_blossom[u] = true;
+                self.in_current_blossom[x] = true;
+                a = self.parent_base(x);
             }
-            if v != self.s {
-                v = self.basis[self.mirror(v)];
-                self.in_current_blossom[v] = true;
-                let e = self.pred[v].clone().expect(
-                    format!("    Tried to unwrap pred[{}], but it's not defined!", v).as_str(),
-                );
-                v = self.basis[e.from()];
-                p2.push(e);
-
-                if self.in_current_blossom[v] {
-                    p2.pop();
-                    self.in_current_blossom[v] = false;
-                    while let Some(e) = p1.last() {
-                        let uu = self.basis[e.from()];
-                        self.in_current_blossom[uu] = false;
-                        p1.pop();
-                        if uu == v {
-                            break;
-                        }
-                    }
-                    return (v, p1, p2);
+            if let Some(y) = b {
+                if self.in_current_blossom[y] {
+                    break y;
                 }
-                self.in_current_blossom[v] = true;
+                self.in_current_blossom[y] = true;
+                b = self.parent_base(y);
+            }
+        };
+
+        for start in [self.basis[u], self.basis[v]] {
+            let mut x = Some(start);
+            while let Some(y) = x.filter(|y| self.in_current_blossom[*y]) {
+                self.in_current_blossom[y] = false;
+                x = self.parent_base(y);
             }
         }
+        base
     }
 
-    fn set_edge_bases(&mut self, b: usize, path: &Vec<E>) {
-        for e in path {
-            let u = e.from();
-            let m = self.mirror(u);
+    // Merge the blossoms from e.from() down to the base b, and return the odd vertices that became even.
+    fn shrink(&mut self, e: &E, b: usize, delta: W) -> Vec<usize> {
+        let mut evens = Vec::new();
+        let mut base = self.basis[e.from()];
+        while base != b {
+            let u = self.mirror(base);
+            self.d_plus[u] = Finite(delta) - self.d_minus[u];
+            self.bridge[u] = Some(e.clone());
+            let below = self.parent_base(base).unwrap();
+            self.basis.set_base(base, b);
             self.basis.set_base(u, b);
-            self.basis.set_base(m, b);
+            evens.push(u);
+            base = below;
         }
+        evens
     }
 
-    fn set_blossom_values(&mut self, path: &Vec<E>) -> Vec<usize> {
-        let mut ret = Vec::new();
-        for e in path {
-            let u = e.from();
-            let v = e.to();
-            let w = e.weight();
-            self.in_current_blossom[u] = false;
-            self.in_current_blossom[v] = false;
-
-            // We can set a d_minus
-            if self.d_plus[v] + Finite(w) < self.d_minus[u] {
-                self.d_minus[u] = self.d_plus[v] + Finite(w);
-                self.pred[u] = Some(e.reverse());
-            }
-
-            let m = self.mirror(u);
-            // We can set a d_plus, and scan it
-            if self.d_minus[u] < self.d_plus[m] {
-                self.d_plus[m] = self.d_minus[u];
-                ret.push(m);
+    /**
+    The even alternating path from w to v, where w is an even vertex on the path to v.
+    Vertices that became even in a blossom are reached by going around the blossom,
+    through its closing edge and back down the other side.
+    */
+    fn path(&self, v: usize, w: usize) -> Vec<E> {
+        let mut reversed = Vec::new();
+        let mut v = v;
+        while v != w {
+            match self.bridge[v].clone() {
+                None => {
+                    let e = self.pred[self.mirror(v)].clone().unwrap();
+                    v = e.from();
+                    reversed.push(e);
+                }
+                Some(e) => {
+                    let back = self.path(e.from(), self.mirror(v));
+                    reversed.extend(back.into_iter().map(|e| e.reverse()));
+                    reversed.push(e.reverse());
+                    v = e.to();
+                }
             }
         }
+        reversed.reverse();
+        reversed
+    }
 
-        ret
+    /// The edge of G that an edge of the mirror graph is a copy of.
+    fn unmirror(&self, e: E) -> E {
+        if e.from() < self.orig_n {
+            e
+        } else {
+            e.shift_by(-(self.orig_n as i64))
+        }
     }
 
     fn mirror(&self, u: usize) -> usize {
@@ -335,3 +424,122 @@ impl<W: Weight, E: Edge<W>> DerigsAlgorithm<W, E> {
         }
     }
 }
+
+#[cfg(test)]
+mod test_odd_path {
+    use crate::algorithm::odd_path::{parity_distances, shortest_even_path, shortest_odd_path};
+    use crate::structure::graph::edge::{BasicEdge, Edge};
+    use crate::structure::graph::undirected_graph::UndirectedGraph;
+    use crate::structure::path_result::PathResult;
+    use crate::structure::path_result::PathResult::*;
+    use crate::structure::weight::Weighted;
+    use crate::utility::random::Random;
+
+    fn graph(n: usize, edges: &[(usize, usize, u64)]) -> UndirectedGraph<u64, BasicEdge<u64>> {
+        let mut graph = UndirectedGraph::new(n);
+        for &(u, v, w) in edges {
+            graph.add_edge(BasicEdge::new(u, v, w));
+        }
+        graph
+    }
+
+    fn cost(graph: &UndirectedGraph<u64, BasicEdge<u64>>, s: usize, t: usize) -> Option<u64> {
+        match shortest_odd_path(graph, s, t) {
+            Possible { cost, .. } => Some(cost),
+            Impossible => None,
+        }
+    }
+
+    #[test]
+    fn test_nested_blossom() {
+        // The only odd 3-4-path is 3-0-5-4, which goes around a blossom based in the mirror of 5.
+        let g = graph(
+            6,
+            &[
+                (0, 3, 5),
+                (0, 5, 2),
+                (0, 1, 1),
+                (1, 5, 1),
+                (4, 5, 3),
+                (3, 5, 2),
+            ],
+        );
+        assert_eq!(cost(&g, 3, 4), Some(10));
+    }
+
+    #[test]
+    fn test_parallel_edges() {
+        let g = graph(3, &[(0, 1, 5), (0, 1, 1), (1, 2, 1), (0, 2, 9)]);
+        assert_eq!(cost(&g, 0, 1), Some(1));
+        assert_eq!(cost(&g, 0, 2), Some(9));
+        assert_eq!(cost(&graph(2, &[]), 0, 1), None);
+    }
+
+    #[test]
+    fn test_even_path() {
+        let g = graph(4, &[(0, 1, 1), (1, 2, 1), (0, 2, 5), (2, 3, 1)]);
+        match shortest_even_path(&g, 0, 2) {
+            Possible { cost, path } => {
+                assert_eq!(cost, 2);
+                assert_eq!(path.iter().map(|e| e.to()).collect::<Vec<_>>(), vec![1, 2]);
+            }
+            Impossible => panic!("0-1-2 is an even path"),
+        }
+        // Every 0-3-path ends with 2-3, after 0-2 or 0-1-2.
+        assert!(matches!(
+            shortest_even_path(&g, 0, 3),
+            Possible { cost: 6, .. }
+        ));
+        assert!(
+            matches!(shortest_even_path(&g, 1, 1), Possible { cost: 0, ref path } if path.is_empty())
+        );
+        assert!(matches!(
+            shortest_even_path(&graph(2, &[(0, 1, 1)]), 0, 1),
+            Impossible
+        ));
+        assert!(matches!(
+            shortest_even_path(&graph(3, &[]), 0, 2),
+            Impossible
+        ));
+    }
+
+    #[test]
+    fn test_parity_distances_match_single_targets() {
+        let mut random = Random::new(48);
+        for _ in 0..40 {
+            let mut edges = Vec::new();
+            for u in 0..9 {
+                for v in u + 1..9 {
+                    if random.below(4) == 0 {
+                        edges.push((u, v, 1 + random.below(9) as u64));
+                    }
+                }
+            }
+            let g = graph(9, &edges);
+            let s = random.below(9);
+            let distances = parity_distances(&g, s);
+            for v in 0..9 {
+                let single = |result: PathResult<u64, BasicEdge<u64>>| match result {
+                    Possible { cost, .. } => Some(cost),
+                    Impossible => None,
+                };
+                assert_eq!(distances.odd(v), single(shortest_odd_path(&g, s, v)));
+                assert_eq!(distances.even(v), single(shortest_even_path(&g, s, v)));
+                for (parity, result) in [(1, distances.odd_path(v)), (0, distances.even_path(v))] {
+                    if let Possible { cost, path } = result {
+                        assert_eq!(path.len() % 2, parity);
+                        assert_eq!(path.iter().map(|e| e.weight()).sum::<u64>(), cost);
+                        assert!(path.windows(2).all(|w| w[0].to() == w[1].from()));
+                        let mut visited: Vec<usize> = path.iter().map(|e| e.to()).collect();
+                        visited.push(s);
+                        visited.sort();
+                        visited.dedup();
+                        assert_eq!(visited.len(), path.len() + 1);
+                        assert_eq!(path.first().map_or(v, |e| e.from()), s);
+                        assert_eq!(path.last().map_or(s, |e| e.to()), v);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::algorithm::k_best_diversion::diversion_alternatives;
+use crate::algorithm::network_diversion::{protected_diversion, DiversionError};
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::weight::Weight;
+use crate::utility::misc::debug;
+
+pub struct OptimalDiversions<W: Weight> {
+    pub cost: W,
+    /// Distinct minimal diversion sets of minimum cost, at most `limit` of them.
+    pub sets: Vec<Vec<PlanarEdge<W>>>,
+    /// Whether `sets` holds every optimal diversion set, or the enumeration stopped at the limit.
+    pub complete: bool,
+    /// The edges that every optimal diversion set cuts, which is exact even if the enumeration is not complete.
+    pub in_every: Vec<PlanarEdge<W>>,
+    /// The edges that some optimal diversion set cuts, which may miss some edges if the enumeration is not complete.
+    pub in_some: Vec<PlanarEdge<W>>,
+}
+
+impl<W: Weight> OptimalDiversions<W> {
+    pub fn is_unique(&self) -> bool {
+        self.complete && self.sets.len() == 1
+    }
+}
+
+/**
+Problem: All Optimal Planar Network Diversions
+In: a planar graph G, two vertices s,t in V(G), a diversion edge b in E(G), and a limit
+Out: the minimum cost, up to `limit` distinct minimal diversion sets of that cost,
+     whether those are all of them, and the edges in every and in some optimal diversion set.
+
+The optimal sets are the first ones from `diversion_alternatives`, so we stop at the first more expensive one.
+An edge e is in every optimal set exactly when protecting e makes the diversion more expensive,
+so `in_every` is checked with `protected_diversion` for the edges that all enumerated sets share.
+Returns an error if no diversion set exists, or the embedding is not planar.
+*/
+pub fn optimal_diversions<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    b: (usize, usize),
+    limit: usize,
+) -> Result<OptimalDiversions<W>, DiversionError> {
+    let mut alternatives = diversion_alternatives(planar, s, t, b)?;
+    let (cost, first) = alternatives.next().ok_or(DiversionError::NoDiversion)?;
+    let mut sets = vec![first];
+    let mut complete = true;
+    for (other, cut) in alternatives {
+        if other != cost {
+            break;
+        }
+        if sets.len() == limit.max(1) {
+            complete = false;
+            break;
+        }
+        sets.push(cut);
+    }
+
+    let same = |e: &PlanarEdge<W>, f: &PlanarEdge<W>| {
+        (e.from(), e.to()) == (f.from(), f.to()) || (e.to(), e.from()) == (f.from(), f.to())
+    };
+    let shared: Vec<PlanarEdge<W>> = sets[0]
+        .iter()
+        .filter(|e| sets.iter().all(|cut| cut.iter().any(|f| same(e, f))))
+        .cloned()
+        .collect();
+    let in_every = if complete {
+        shared
+    } else {
+        shared
+            .into_iter()
+            .filter(|e| {
+                protected_diversion(planar, s, t, b, &[(e.from(), e.to())])
+                    .map_or(true, |(other, _)| other != cost)
+            })
+            .collect()
+    };
+    let mut in_some: Vec<PlanarEdge<W>> = Vec::new();
+    for e in sets.iter().flatten() {
+        if !in_some.iter().any(|f| same(e, f)) {
+            in_some.push(e.clone());
+        }
+    }
+
+    debug(format!(
+        "Found {}{} optimal diversion sets of cost {}, {} edges are in every one of them and {} in some.",
+        sets.len(),
+        if complete { "" } else { " or more" },
+        cost,
+        in_every.len(),
+        in_some.len()
+    ));
+    Ok(OptimalDiversions {
+        cost,
+        sets,
+        complete,
+        in_every,
+        in_some,
+    })
+}
+
+#[cfg(test)]
+mod test_optimal_diversion {
+    use crate::algorithm::k_best_diversion::k_best_diversions;
+    use crate::algorithm::optimal_diversion::optimal_diversions;
+    use crate::structure::graph::edge::Edge;
+    use crate::structure::graph::planar_edge::PlanarEdge;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::utility::generator::grid;
+
+    fn pairs(edges: &[PlanarEdge<u64>]) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<_> = edges
+            .iter()
+            .map(|e| (e.from().min(e.to()), e.from().max(e.to())))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn test_example_is_unique() {
+        let planar: PlanarGraph<f64> = std::fs::read_to_string("data/example.in")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let optimal = optimal_diversions(&planar, 0, 9, (7, 8), 10).unwrap();
+        assert!(optimal.is_unique());
+        assert_eq!(optimal.cost, 3.0);
+        assert_eq!(optimal.in_every.len(), 3);
+        assert_eq!(optimal.in_some.len(), 3);
+    }
+
+    #[test]
+    fn test_unit_grid_has_many_optima() {
+        // Cutting 0-1 together with 4-8 or with 8-9 leaves only the paths through 4-5.
+        let planar: PlanarGraph<u64> = grid(4, 3).to_string().parse().unwrap();
+        let all = optimal_diversions(&planar, 0, 11, (4, 5), 100).unwrap();
+        assert!(all.complete && !all.is_unique());
+        assert_eq!((all.cost, all.sets.len()), (2, 2));
+        assert_eq!(pairs(&all.in_every), vec![(0, 1)]);
+        assert_eq!(pairs(&all.in_some), vec![(0, 1), (4, 8), (8, 9)]);
+        let cheapest = k_best_diversions(&planar, 0, 11, (4, 5), 100).unwrap();
+        let count = cheapest.iter().filter(|(c, _)| *c == all.cost).count();
+        assert_eq!(all.sets.len(), count);
+
+        let truncated = optimal_diversions(&planar, 0, 11, (4, 5), 1).unwrap();
+        assert!(!truncated.complete && !truncated.is_unique());
+        assert_eq!(pairs(&truncated.in_every), pairs(&all.in_every));
+    }
+}
@@ -0,0 +1,371 @@
+use crate::algorithm::k_best_diversion::diversion_alternatives;
+use crate::algorithm::lexicographic_diversion::{lexicographic_diversion, TieBreak};
+use crate::algorithm::network_diversion::{network_diversion, DiversionError};
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::weight::{Lexicographic, Weight};
+use crate::utility::misc::debug;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum ClosureError {
+    /// No diversion set exists at all.
+    NoDiversion,
+    /// Every diversion set closes more edges, at least this many.
+    TooFewClosures(usize),
+    /// The enumeration of diversion sets stopped at this many sets before it could prove the answer.
+    LimitReached(usize),
+    /// The embedding has this genus, but the search needs a plane embedding.
+    NotPlanar(usize),
+}
+
+impl Display for ClosureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClosureError::NoDiversion => write!(f, "No diversion set exists"),
+            ClosureError::TooFewClosures(k) => {
+                write!(f, "Every diversion set closes at least {} edges", k)
+            }
+            ClosureError::LimitReached(limit) => write!(
+                f,
+                "The search stopped after {} diversion sets, before it found the cheapest one that closes few enough edges",
+                limit
+            ),
+            ClosureError::NotPlanar(genus) => write!(f, "{}", DiversionError::NotPlanar(*genus)),
+        }
+    }
+}
+
+impl From<DiversionError> for ClosureError {
+    fn from(err: DiversionError) -> Self {
+        match err {
+            DiversionError::NoDiversion => ClosureError::NoDiversion,
+            DiversionError::NotPlanar(genus) => ClosureError::NotPlanar(genus),
+        }
+    }
+}
+
+/// A diversion set with its cost.
+type Diversion<W> = (W, Vec<PlanarEdge<W>>);
+
+/// The diversion sets that are not beaten in both cost and number of closures, by increasing cost.
+pub struct ParetoFront<W: Weight> {
+    pub points: Vec<Diversion<W>>,
+    /// Whether the front is exact, or some gaps were not searched to the end within the limit.
+    pub complete: bool,
+}
+
+/**
+Problem: Fewest Closures
+In: a planar graph G, two vertices s,t in V(G), and a diversion edge b in E(G)
+Out: among the diversion sets with the fewest edges, a cheapest one,
+     or an error if no diversion set exists or the embedding is not planar.
+
+Every edge weighs (1, w(e)), compared lexicographically, so the odd path counts the closures first.
+*/
+pub fn fewest_closures<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    b: (usize, usize),
+) -> Result<Diversion<W>, DiversionError> {
+    if !planar.is_planar() {
+        return Err(DiversionError::NotPlanar(planar.genus()));
+    }
+    let counted = planar.map_weights(|w| Lexicographic::<u64, W>(1, w));
+    let (_, cut) = network_diversion(&counted, s, t, b).ok_or(DiversionError::NoDiversion)?;
+    Ok(unpriced(planar, &cut))
+}
+
+/// The sum of k times the given cost.
+fn times<W: Weight>(price: W, k: usize) -> W {
+    (0..k).fold(0.into(), |acc, _| acc + price)
+}
+
+/**
+The price of a closure at which the diversion sets a and b are equally expensive, where a is cheaper but closes
+more edges. It is the fraction (cost(b) - cost(a)) / (closures(a) - closures(b)), which integer weights can't hold,
+so every cost with this price is scaled by the denominator instead.
+*/
+struct ClosurePrice<W: Weight> {
+    cost: W,
+    closures: usize,
+}
+
+impl<W: Weight> ClosurePrice<W> {
+    fn new(a: &Diversion<W>, b: &Diversion<W>) -> Self {
+        ClosurePrice {
+            cost: b.0 - a.0,
+            closures: a.1.len() - b.1.len(),
+        }
+    }
+
+    /// The scaled cost of a set of the given cost and number of closures.
+    fn scaled(&self, cost: W, closures: usize) -> W {
+        times(cost, self.closures) + times(self.cost, closures)
+    }
+
+    /// The graph where every edge costs its scaled weight plus the scaled price, so a diversion set costs
+    /// `scaled(cost, closures)`.
+    fn priced(&self, planar: &PlanarGraph<W>) -> PlanarGraph<W> {
+        planar.map_weights(|w| self.scaled(w, 1))
+    }
+}
+
+/// A diversion set of the graph where every closure costs extra, with the weights and cost of G.
+/// The weights are looked up rather than computed back, so floating point costs are not rounded.
+fn unpriced<W: Weight, V: Weight>(planar: &PlanarGraph<W>, cut: &[PlanarEdge<V>]) -> Diversion<W> {
+    let cut: Vec<PlanarEdge<W>> = cut
+        .iter()
+        .map(|e| {
+            let weight = planar.real().edge(e.from(), e.to()).weight;
+            e.with_weight(weight)
+        })
+        .collect();
+    (cut.iter().fold(0.into(), |acc, e| acc + e.weight), cut)
+}
+
+/// The supported points of the front strictly between a and b, which minimize cost + price * closures for some price.
+fn supported<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    b: (usize, usize),
+    (left, right): (&Diversion<W>, &Diversion<W>),
+    points: &mut Vec<Diversion<W>>,
+) {
+    if left.1.len() <= right.1.len() + 1 {
+        return;
+    }
+    let price = ClosurePrice::new(left, right);
+    let Some(found) = network_diversion(&price.priced(planar), s, t, b) else {
+        return;
+    };
+    // Below both ends, the new set can not be dominated by either of them.
+    let combined = found.0;
+    if combined < price.scaled(left.0, left.1.len())
+        && combined < price.scaled(right.0, right.1.len())
+    {
+        let middle = unpriced(planar, &found.1);
+        supported(planar, s, t, b, (left, &middle), points);
+        supported(planar, s, t, b, (&middle, right), points);
+        points.push(middle);
+    }
+}
+
+/// The cheapest and the fewest-closures end of the front, and the supported points between them, by increasing cost.
+fn hull<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    b: (usize, usize),
+) -> Result<Vec<Diversion<W>>, DiversionError> {
+    let cheapest = lexicographic_diversion(planar, s, t, b, TieBreak::FewestEdges)?;
+    let fewest = fewest_closures(planar, s, t, b)?;
+    let mut points = Vec::new();
+    supported(planar, s, t, b, (&cheapest, &fewest), &mut points);
+    if fewest.1.len() < cheapest.1.len() {
+        points.push(fewest);
+    }
+    points.push(cheapest);
+    sort_front(&mut points);
+    Ok(points)
+}
+
+/// By increasing cost, and by closures for the same cost.
+fn sort_front<W: Weight>(points: &mut [Diversion<W>]) {
+    points.sort_by(|x, y| {
+        x.0.partial_cmp(&y.0)
+            .unwrap()
+            .then(x.1.len().cmp(&y.1.len()))
+    });
+}
+
+/**
+Problem: Pareto Planar Network Diversion
+In: a planar graph G, two vertices s,t in V(G), a diversion edge b in E(G), and a limit
+Out: the Pareto front of (cost, number of closures) over all diversion sets, with one diversion set per point.
+
+With a price for every closure, the shortest odd path finds a point on the front, so we first find the supported points,
+where the front bends, by recursing on the price at which two neighbouring points cost the same.
+A point between two supported points a and b costs less than b and closes fewer edges than a,
+so with the price of (a, b) it costs less than cost(b) + price * closures(a).
+We enumerate `diversion_alternatives` with that price up to that bound, or up to `limit` sets per gap.
+Returns an error if no diversion set exists, or the embedding is not planar.
+*/
+pub fn pareto_diversions<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    b: (usize, usize),
+    limit: usize,
+) -> Result<ParetoFront<W>, DiversionError> {
+    let hull = hull(planar, s, t, b)?;
+    let mut candidates = hull.clone();
+    let mut complete = true;
+    for pair in hull.windows(2) {
+        let (left, right) = (&pair[0], &pair[1]);
+        if left.1.len() <= right.1.len() + 1 {
+            continue;
+        }
+        let price = ClosurePrice::new(left, right);
+        let bound = price.scaled(right.0, left.1.len());
+        for (i, found) in diversion_alternatives(&price.priced(planar), s, t, b)?.enumerate() {
+            if found.0 >= bound {
+                break;
+            }
+            if i == limit {
+                complete = false;
+                break;
+            }
+            candidates.push(unpriced(planar, &found.1));
+        }
+    }
+
+    sort_front(&mut candidates);
+    let mut points: Vec<Diversion<W>> = Vec::new();
+    for point in candidates {
+        if points
+            .last()
+            .is_none_or(|last| point.1.len() < last.1.len())
+        {
+            points.push(point);
+        }
+    }
+    debug(format!(
+        "The Pareto front of ({}, {}) has {} points, {} of them supported.",
+        b.0,
+        b.1,
+        points.len(),
+        hull.len()
+    ));
+    Ok(ParetoFront { points, complete })
+}
+
+/**
+Problem: Planar Network Diversion with at most k closures
+In: a planar graph G, two vertices s,t in V(G), a diversion edge b in E(G), a number k, and a limit
+Out: a cheapest diversion set with at most k edges.
+
+The supported points around k give an upper bound, and a price for the closures. With that price,
+a set X with at most k closures costs at least its combined cost minus price * k, so we enumerate
+`diversion_alternatives` with that price until this exceeds the best set so far, or for at most `limit` sets.
+*/
+pub fn cheapest_with_closures<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    b: (usize, usize),
+    k: usize,
+    limit: usize,
+) -> Result<Diversion<W>, ClosureError> {
+    let hull = hull(planar, s, t, b)?;
+    let i = match hull.iter().position(|(_, cut)| cut.len() <= k) {
+        None => return Err(ClosureError::TooFewClosures(hull.last().unwrap().1.len())),
+        Some(0) => return Ok(hull[0].clone()),
+        Some(i) => i,
+    };
+    let mut best = hull[i].clone();
+    let price = ClosurePrice::new(&hull[i - 1], &hull[i]);
+    for (i, found) in diversion_alternatives(&price.priced(planar), s, t, b)?.enumerate() {
+        if found.0 >= price.scaled(best.0, k) {
+            break;
+        }
+        if i == limit {
+            return Err(ClosureError::LimitReached(limit));
+        }
+        let found = unpriced(planar, &found.1);
+        if found.1.len() <= k && found.0 < best.0 {
+            best = found;
+        }
+    }
+    Ok(best)
+}
+
+#[cfg(test)]
+mod test_pareto_diversion {
+    use crate::algorithm::k_best_diversion::k_best_diversions;
+    use crate::algorithm::pareto_diversion::{
+        cheapest_with_closures, fewest_closures, hull, pareto_diversions, ClosureError,
+    };
+    use crate::algorithm::test_utility::random_grid_with_edge;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_front_matches_all_diversions() {
+        let mut random = Random::new(19);
+        for _ in 0..20 {
+            let (planar, (u, v)) = random_grid_with_edge::<f64>(&mut random, 4, 4, 9);
+
+            let all = k_best_diversions(&planar, 0, 15, (u, v), 100_000).unwrap();
+            let front = pareto_diversions(&planar, 0, 15, (u, v), 100_000).unwrap();
+            assert!(front.complete);
+            assert!(front.points.windows(2).all(|w| w[0].0 < w[1].0));
+            for k in 0..8 {
+                // The cheapest set with at most k edges, and the front point it falls under.
+                let best = all.iter().find(|(_, cut)| cut.len() <= k).map(|(c, _)| *c);
+                let on_front = front
+                    .points
+                    .iter()
+                    .find(|(_, cut)| cut.len() <= k)
+                    .map(|(c, _)| *c);
+                assert_eq!(best, on_front);
+                match cheapest_with_closures(&planar, 0, 15, (u, v), k, 100_000) {
+                    Ok((cost, cut)) => assert_eq!((Some(cost), cut.len() <= k), (best, true)),
+                    Err(ClosureError::TooFewClosures(fewest)) => {
+                        assert!(best.is_none());
+                        let (_, cut) = fewest_closures(&planar, 0, 15, (u, v)).unwrap();
+                        assert_eq!(fewest, cut.len());
+                    }
+                    Err(err) => panic!("{}", err),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_integer_prices_find_every_supported_point() {
+        let mut random = Random::new(38);
+        for _ in 0..30 {
+            let (planar, (u, v)) = random_grid_with_edge::<u64>(&mut random, 4, 4, 9);
+            let Ok(found) = hull(&planar, 0, 15, (u, v)) else {
+                continue;
+            };
+
+            // The cheapest cost for every number of closures, from the most closures to the fewest.
+            let all = k_best_diversions(&planar, 0, 15, (u, v), 100_000).unwrap();
+            let (most, fewest) = (found[0].1.len(), found.last().unwrap().1.len());
+            let best: Vec<(usize, u64)> = (fewest..=most)
+                .rev()
+                .filter_map(|k| {
+                    let cost = all
+                        .iter()
+                        .filter(|(_, cut)| cut.len() == k)
+                        .map(|(c, _)| *c)
+                        .min()?;
+                    Some((k, cost))
+                })
+                .collect();
+            // The corners of the lower convex hull, in exact integer arithmetic.
+            let mut corners: Vec<(usize, u64)> = Vec::new();
+            for &p in &best {
+                while corners.len() >= 2 {
+                    let (a, b) = (corners[corners.len() - 2], corners[corners.len() - 1]);
+                    // b is no corner if it is not strictly below the line from a to p.
+                    let (ab_k, ab_c) = ((a.0 - b.0) as i64, b.1 as i64 - a.1 as i64);
+                    let (ap_k, ap_c) = ((a.0 - p.0) as i64, p.1 as i64 - a.1 as i64);
+                    if ab_c * ap_k >= ap_c * ab_k {
+                        corners.pop();
+                    } else {
+                        break;
+                    }
+                }
+                corners.push(p);
+            }
+
+            let found: Vec<(usize, u64)> = found.iter().map(|(c, cut)| (cut.len(), *c)).collect();
+            assert_eq!(found, corners);
+        }
+    }
+}
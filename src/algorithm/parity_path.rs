@@ -0,0 +1,82 @@
+use crate::algorithm::odd_path::{shortest_even_path, shortest_odd_path};
+use crate::algorithm::utility::split_edges;
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::path_result::{PathResult, PathResult::*};
+use crate::structure::weight::Weight;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+/**
+Problem: Shortest Path with Parity
+In: an undirected graph G, two vertices s,t in V(G), a set of marked edges F of E(G), and a parity
+Out: the shortest simple s-t-path in G that uses a number of edges of F with that parity, if one exists.
+
+`split_edges` subdivides every edge that is not marked, so the number of edges of a path in the split graph has
+the parity of the number of marked edges that it uses. The shortest odd or even path there is mapped back to
+the edges of G, where the two halves of a subdivided edge become the edge itself.
+The marked edges are compared by their endpoints and weight, in either direction.
+*/
+pub fn shortest_path_with_parity<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+    marked: &[E],
+    parity: Parity,
+) -> PathResult<W, E> {
+    let (split, map) = split_edges(graph, marked.to_vec());
+    let result = match parity {
+        Parity::Odd => shortest_odd_path(&split, s, t),
+        Parity::Even => shortest_even_path(&split, s, t),
+    };
+    match result {
+        Impossible => Impossible,
+        Possible { cost, path } => Possible {
+            cost,
+            path: path.iter().flat_map(map).collect(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test_parity_path {
+    use crate::algorithm::parity_path::{shortest_path_with_parity, Parity};
+    use crate::algorithm::test_utility::{brute_force, random_graph};
+    use crate::structure::graph::edge::{BasicEdge, Edge};
+    use crate::structure::path_result::PathResult::*;
+    use crate::structure::weight::Weighted;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut random = Random::new(47);
+        for _ in 0..50 {
+            let graph = random_graph(&mut random);
+            let marked: Vec<_> = graph
+                .edges()
+                .into_iter()
+                .filter(|e| e.from() < e.to() && random.below(3) == 0)
+                .collect();
+            let crossings = |path: &[BasicEdge<u64>]| {
+                path.iter()
+                    .filter(|e| marked.contains(e) || marked.contains(&e.reverse()))
+                    .count()
+            };
+            for (parity, remainder) in [(Parity::Even, 0), (Parity::Odd, 1)] {
+                let expected = brute_force(&graph, 0, 6, &|path| crossings(path) % 2 == remainder);
+                match shortest_path_with_parity(&graph, 0, 6, &marked, parity) {
+                    Possible { cost, path } => {
+                        assert_eq!(Some(cost), expected);
+                        assert_eq!(path.iter().map(|e| e.weight()).sum::<u64>(), cost);
+                        assert!(path.windows(2).all(|w| w[0].to() == w[1].from()));
+                    }
+                    Impossible => assert_eq!(expected, None),
+                }
+            }
+        }
+    }
+}
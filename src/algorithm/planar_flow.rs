@@ -0,0 +1,340 @@
+use crate::algorithm::network_diversion::network_diversion;
+use crate::algorithm::odd_cycle::shortest_odd_cycle;
+use crate::algorithm::shortest_path::shortest_path_tree;
+use crate::algorithm::utility::split_edges;
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::path_result::PathResult::*;
+use crate::structure::weight::Weight;
+use crate::utility::misc::repeat;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum PlanarFlowError {
+    /// s and t are the same vertex, so there is nothing to cut.
+    SameVertex,
+    /// The embedding has this genus, and on a surface other than the plane, a cycle in the dual need not be a cut.
+    NotPlanar(usize),
+}
+
+impl Display for PlanarFlowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanarFlowError::SameVertex => write!(f, "s and t must be different vertices"),
+            PlanarFlowError::NotPlanar(genus) => write!(
+                f,
+                "The embedding has genus {}, but this needs a plane embedding",
+                genus
+            ),
+        }
+    }
+}
+
+/// A flow value with the flow through every edge.
+type Flow<W> = (W, Vec<(PlanarEdge<W>, W)>);
+
+/**
+Problem: Planar Minimum S-T-cut
+In: a planar graph G, two different vertices s,t in V(G), where the weights are the capacities
+Out: the value of a minimum s-t-cut, and its edges.
+
+A minimal s-t-cut is a cycle in the dual around s or t, so it crosses an s-t-path P an odd number of times, and every
+dual cycle that crosses P an odd number of times separates s from t. So when the dual edges of P are marked, a minimum
+cut is a shortest cycle with an odd number of marked edges, which `split_edges` turns into an odd cycle. A bridge on P
+is a loop in the dual, which is left out there, but it is a cut on its own.
+*/
+pub fn planar_min_cut<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+) -> Result<(W, Vec<PlanarEdge<W>>), PlanarFlowError> {
+    let path = terminal_path(planar, s, t)?;
+    Ok(cut_across(planar, &path))
+}
+
+/**
+Problem: Planar Minimum S-T-cut through an edge
+In: a planar graph G, two different vertices s,t in V(G), and an edge b in E(G)
+Out: the value of a minimum s-t-cut that contains b, and its edges.
+
+A cut C with b is b together with a cut of G - b. Such a cut X either already separates s from t in G, or every
+s-t-path of G - X uses b, so X is a diversion set of b: the dual odd path of `network_diversion` between the faces
+of b. So the cheapest is b with the cheaper of a minimum cut and a minimum diversion set.
+*/
+pub fn planar_min_cut_through<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    (bu, bv): (usize, usize),
+) -> Result<(W, Vec<PlanarEdge<W>>), PlanarFlowError> {
+    let b = planar.real().edge(bu, bv);
+    let (mut cost, mut cut) = planar_min_cut(planar, s, t)?;
+    if let Some((diversion, set)) = network_diversion(planar, s, t, (bu, bv)) {
+        if diversion <= cost {
+            (cost, cut) = (diversion, set);
+        }
+    }
+    // With a weight of zero, b may already be in the minimum cut.
+    cut.retain(|e| (e.from(), e.to()) != (bu, bv) && (e.from(), e.to()) != (bv, bu));
+    cut.push(b.clone());
+    Ok((cost + b.weight, cut))
+}
+
+/**
+Problem: Planar Maximum S-T-flow
+In: a planar graph G, two different vertices s,t in V(G), where the weights are the capacities
+Out: the value of a maximum s-t-flow, and the flow through every edge, with the edge in the direction of its flow.
+
+A flow of value λ is λ along the s-t-path P of `planar_min_cut` plus a circulation, and in the plane, a circulation is
+the difference p(right) - p(left) of potentials on the faces on both sides of every edge. The capacities bound that
+difference both ways, so the potentials are distances in the dual, where crossing P from its left to its right is
+λ shorter, and crossing it back λ longer. With λ the minimum cut, no dual cycle gets negative, since a simple cycle
+crosses P at most once more one way than the other, and then it is a cut (Hassin and Johnson). The lengths may be
+negative, so Bellman-Ford finds the distances, each kept as a difference of two weights.
+*/
+pub fn planar_max_flow<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+) -> Result<Flow<W>, PlanarFlowError> {
+    let path = terminal_path(planar, s, t)?;
+    let (value, _) = cut_across(planar, &path);
+    let zero = W::from(0);
+    // The flow along P that an edge carries forwards and backwards.
+    let along = |e: &PlanarEdge<W>| {
+        (
+            if path.contains(e) { value } else { zero },
+            if path.contains(&e.reverse()) {
+                value
+            } else {
+                zero
+            },
+        )
+    };
+    let edges: Vec<_> = planar
+        .real()
+        .edges()
+        .into_iter()
+        .filter(|e| e.from() < e.to())
+        .collect();
+
+    // The potential of a face is dist.0 - dist.1.
+    let mut dist = repeat(planar.f(), (zero, zero));
+    for _ in 0..planar.f() {
+        let mut changed = false;
+        for e in &edges {
+            let (forward, backward) = along(e);
+            let arcs = [
+                (e.left(), e.right(), e.weight + backward, forward),
+                (e.right(), e.left(), e.weight + forward, backward),
+            ];
+            for (from, to, plus, minus) in arcs {
+                let (a, b) = (dist[from].0 + plus, dist[from].1 + minus);
+                if a + dist[to].1 < dist[to].0 + b {
+                    dist[to] = (a, b);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let flows = edges
+        .into_iter()
+        .map(|e| {
+            let (forward, backward) = along(&e);
+            let (left, right) = (dist[e.left()], dist[e.right()]);
+            let (there, back) = (right.0 + left.1 + forward, right.1 + left.0 + backward);
+            if back <= there {
+                (e, there - back)
+            } else {
+                (e.reverse(), back - there)
+            }
+        })
+        .collect();
+    Ok((value, flows))
+}
+
+/// Some s-t-path, which is empty when t can't be reached from s.
+fn terminal_path<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+) -> Result<Vec<PlanarEdge<W>>, PlanarFlowError> {
+    if s == t {
+        return Err(PlanarFlowError::SameVertex);
+    }
+    if !planar.is_planar() {
+        return Err(PlanarFlowError::NotPlanar(planar.genus()));
+    }
+    let (dist, pred) = shortest_path_tree(planar.real(), s);
+    let mut path = Vec::new();
+    if dist[t].is_infinite() {
+        return Ok(path);
+    }
+    let mut v = t;
+    while let Some(e) = &pred[v] {
+        path.push(e.clone());
+        v = e.from();
+    }
+    path.reverse();
+    Ok(path)
+}
+
+/// A minimum cut among the sets of edges that an odd number of edges of `path` cross, see `planar_min_cut`.
+fn cut_across<W: Weight>(
+    planar: &PlanarGraph<W>,
+    path: &[PlanarEdge<W>],
+) -> (W, Vec<PlanarEdge<W>>) {
+    let crossed = path.iter().map(|e| e.rotate_right()).collect();
+    let (split, map) = split_edges(planar.dual(), crossed);
+    let mut best = match shortest_odd_cycle(&split) {
+        Impossible => None,
+        // The dual edge of a real edge turns back into it, in the other direction.
+        Possible { cost, path: cycle } => Some((
+            cost,
+            cycle
+                .iter()
+                .flat_map(&map)
+                .map(|d| d.rotate_right())
+                .collect(),
+        )),
+    };
+    for e in path.iter().filter(|e| e.left() == e.right()) {
+        if best.as_ref().is_none_or(|(cost, _)| e.weight < *cost) {
+            best = Some((e.weight, vec![e.clone()]));
+        }
+    }
+    best.unwrap_or((W::from(0), Vec::new()))
+}
+
+#[cfg(test)]
+mod test_planar_flow {
+    use crate::algorithm::max_flow::min_cut;
+    use crate::algorithm::planar_flow::{
+        planar_max_flow, planar_min_cut, planar_min_cut_through, PlanarFlowError,
+    };
+    use crate::algorithm::shortest_path::bfs;
+    use crate::algorithm::test_utility::{random_grid, sides};
+    use crate::structure::graph::edge::Edge;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::structure::graph::point::Point;
+    use crate::utility::generator::{grid, torus};
+    use crate::utility::random::Random;
+
+    /// The flows are within the capacities, and only leave s and enter t, with the given value.
+    fn assert_flow(planar: &PlanarGraph<u64>, s: usize, t: usize) {
+        let (expected, _) = min_cut(planar.real(), &[s], &[t], &|e| Some(e.weight));
+        let (value, flows) = planar_max_flow(planar, s, t).unwrap();
+        assert_eq!(value, expected);
+        let mut net = vec![0i64; planar.n()];
+        for (e, flow) in flows {
+            assert!(flow <= e.weight);
+            net[e.from()] -= flow as i64;
+            net[e.to()] += flow as i64;
+        }
+        for (v, net) in net.into_iter().enumerate() {
+            let expected = if v == s {
+                -(value as i64)
+            } else if v == t {
+                value as i64
+            } else {
+                0
+            };
+            assert_eq!(net, expected);
+        }
+    }
+
+    #[test]
+    fn test_matches_augmenting_paths() {
+        let mut random = Random::new(49);
+        for _ in 0..40 {
+            let planar: PlanarGraph<u64> = random_grid(&mut random, 5, 4, 9);
+            let s = random.below(20);
+            let t = (s + 1 + random.below(19)) % 20;
+            let (expected, _) = min_cut(planar.real(), &[s], &[t], &|e| Some(e.weight));
+
+            let (value, cut) = planar_min_cut(&planar, s, t).unwrap();
+            assert_eq!(value, expected);
+            assert_eq!(cut.iter().map(|e| e.weight).sum::<u64>(), value);
+            let mut rest = planar.real().clone();
+            rest.delete_edges(&cut);
+            assert!(bfs(&rest, s)[t].is_infinite());
+            assert_flow(&planar, s, t);
+        }
+    }
+
+    #[test]
+    fn test_bridges_and_other_components() {
+        // A 3x3 grid with a pendant vertex 9 behind 0, and an isolated vertex 10.
+        let mut instance = grid(3, 3);
+        instance.points.push(Point::new(-1.0, 0.0));
+        instance.points.push(Point::new(5.0, 5.0));
+        instance.edges.push((0, 9, 1.0));
+        let planar: PlanarGraph<u64> = instance.to_string().parse().unwrap();
+
+        let (value, cut) = planar_min_cut(&planar, 9, 8).unwrap();
+        assert_eq!(value, 1);
+        assert_eq!(
+            cut.iter().map(|e| (e.from(), e.to())).collect::<Vec<_>>(),
+            vec![(9, 0)]
+        );
+        assert_flow(&planar, 9, 8);
+        assert_flow(&planar, 4, 0);
+        assert_eq!(planar_min_cut(&planar, 10, 4), Ok((0, Vec::new())));
+        assert_flow(&planar, 10, 4);
+
+        assert_eq!(
+            planar_min_cut(&planar, 7, 7),
+            Err(PlanarFlowError::SameVertex)
+        );
+        let (points, rotations) = torus(4, 3);
+        let torus = PlanarGraph::from_rotations(points, rotations)
+            .unwrap()
+            .map_weights(|w| w as u64);
+        assert_eq!(
+            planar_max_flow(&torus, 0, 7).map(|(value, _)| value),
+            Err(PlanarFlowError::NotPlanar(1))
+        );
+    }
+
+    #[test]
+    fn test_cut_through_matches_brute_force() {
+        let mut random = Random::new(50);
+        for _ in 0..20 {
+            let planar: PlanarGraph<u64> = random_grid(&mut random, 4, 4, 9);
+            let edges: Vec<_> = planar
+                .real()
+                .edges()
+                .into_iter()
+                .filter(|e| e.from() < e.to())
+                .collect();
+            let s = random.below(16);
+            let t = (s + 1 + random.below(15)) % 16;
+            let b = edges[random.below(edges.len())].clone();
+
+            // The middle four vertices share no face with most others. Every cut is b with the boundary of a side of s.
+            let expected = sides(16, s, t)
+                .map(|side| {
+                    edges
+                        .iter()
+                        .filter(|e| side[e.from()] != side[e.to()] || **e == b)
+                        .map(|e| e.weight)
+                        .sum::<u64>()
+                })
+                .min()
+                .unwrap();
+
+            let (cost, cut) = planar_min_cut_through(&planar, s, t, (b.from(), b.to())).unwrap();
+            assert_eq!(cost, expected);
+            assert_eq!(cut.iter().map(|e| e.weight).sum::<u64>(), cost);
+            assert!(cut.contains(&b));
+            let mut rest = planar.real().clone();
+            rest.delete_edges(&cut);
+            assert!(bfs(&rest, s)[t].is_infinite());
+        }
+    }
+}
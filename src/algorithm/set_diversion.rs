@@ -0,0 +1,321 @@
+use crate::algorithm::network_diversion::bfs;
+use crate::algorithm::odd_path::shortest_odd_path;
+use crate::algorithm::utility::split_edges;
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::path_result::PathResult::*;
+use crate::structure::weight::Weight;
+use crate::utility::misc::{debug, repeat};
+use std::fmt::{Display, Formatter};
+
+/// The exact fallback enumerates all s-sides of a cut, so it only runs on components this small.
+pub const EXACT_LIMIT: usize = 22;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Solver {
+    /// Shortest odd paths in the dual, one for every edge of B.
+    Planar,
+    /// Exhaustive search over all s-t-cuts, used when the embedding is not planar.
+    Exact,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SetDiversionError {
+    /// No s-t-path uses an edge of B, so no cut can force the paths through B.
+    NotOnAnyPath,
+    /// The embedding is not planar, and the component of s is too large for the exact search.
+    TooLargeForExact(usize),
+}
+
+impl Display for SetDiversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetDiversionError::NotOnAnyPath => {
+                write!(f, "No s-t-path uses an edge of B, so no diversion exists")
+            }
+            SetDiversionError::TooLargeForExact(n) => write!(
+                f,
+                "The embedding is not planar, and its {} vertices are too many for the exact search (at most {})",
+                n, EXACT_LIMIT
+            ),
+        }
+    }
+}
+
+pub struct SetDiversion<W: Weight> {
+    pub cost: W,
+    pub cut: Vec<PlanarEdge<W>>,
+    pub solver: Solver,
+}
+
+/**
+Problem: Planar Set Diversion
+In: a planar graph G, two vertices s,t in V(G), and a set of diversion edges B of E(G)
+Out: a minimum-cost set of edges X, disjoint from B, such that every s-t-path in G - X uses an edge of B,
+     while some s-t-path in G - X still exists.
+
+An optimal X is C - B for a minimal s-t-cut C that contains some b in B, and such a cut is a cycle in the dual through b*.
+We give all edges of B* weight 0, and for every b in B we look for the shortest odd left(b)-right(b)-path
+in the dual, where the parity counts the crossings with an s-t-path that avoids B.
+
+Every component of a plane graph has its own faces, so the dual works for any number of components.
+On a surface of higher genus, we fall back to `exact_set_diversion`, and the result says which solver was used.
+*/
+pub fn set_diversion<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    diversions: &[(usize, usize)],
+) -> Result<SetDiversion<W>, SetDiversionError> {
+    let set: Vec<PlanarEdge<W>> = diversions
+        .iter()
+        .map(|&(du, dv)| planar.real().edge(du, dv))
+        .collect();
+
+    if !planar.is_planar() {
+        debug(format!(
+            "The embedding has genus {}, so we fall back to the exact search.",
+            planar.genus()
+        ));
+        return exact_set_diversion(planar.real(), s, t, &set).map(|(cost, cut)| SetDiversion {
+            cost,
+            cut,
+            solver: Solver::Exact,
+        });
+    }
+
+    let mut avoiding = planar.real().clone();
+    avoiding.delete_edges(&set);
+    // There are no loops in G, so (s, s) is not an edge, and this is a plain search in G - B.
+    let path = match bfs(&avoiding, s, t, (s, s)) {
+        Some(path) => path,
+        None => {
+            debug(format!(
+                "Every {}-{}-path already uses an edge of B, no diversion is needed.",
+                s, t
+            ));
+            return match bfs(planar.real(), s, t, (s, s)) {
+                Some(_) => Ok(SetDiversion {
+                    cost: 0.into(),
+                    cut: Vec::new(),
+                    solver: Solver::Planar,
+                }),
+                None => Err(SetDiversionError::NotOnAnyPath),
+            };
+        }
+    };
+
+    let in_set = |e: &PlanarEdge<W>| {
+        set.iter().any(|b| {
+            (b.from(), b.to()) == (e.from(), e.to()) || (b.to(), b.from()) == (e.from(), e.to())
+        })
+    };
+    let mut dual = UndirectedGraph::new(planar.f());
+    for e in planar.dual().edges() {
+        if e.from() < e.to() {
+            let mut e = e.clone();
+            if in_set(&e.rotate_right()) {
+                e.weight = 0.into();
+            }
+            dual.add_edge(e);
+        }
+    }
+    let marked = path.iter().map(|e| e.rotate_right()).collect();
+    let (split, map) = split_edges(&dual, marked);
+
+    let mut best: Option<(W, Vec<PlanarEdge<W>>)> = None;
+    for b in &set {
+        if let Possible { cost, path } = shortest_odd_path(&split, b.left(), b.right()) {
+            if best.as_ref().is_none_or(|(c, _)| cost < *c) {
+                let cut = path
+                    .iter()
+                    .flat_map(&map)
+                    .map(|e| e.rotate_right())
+                    .filter(|e| !in_set(e))
+                    .collect();
+                best = Some((cost, cut));
+            }
+        }
+    }
+
+    match best {
+        None => {
+            debug(format!(
+                "No diversion set exists, no paths from {} to {} use an edge of B.",
+                s, t
+            ));
+            Err(SetDiversionError::NotOnAnyPath)
+        }
+        Some((cost, cut)) => {
+            debug(format!(
+                "We have to cut {} edges to divert the network through B, with a total cost of {}.",
+                cut.len(),
+                cost
+            ));
+            Ok(SetDiversion {
+                cost,
+                cut,
+                solver: Solver::Planar,
+            })
+        }
+    }
+}
+
+/**
+Problem: Set Diversion (exact, exponential)
+In: any graph G, two vertices s,t in V(G), and a set of diversion edges B of E(G)
+Out: the same as `set_diversion`, found by trying every side S of s in the component of s.
+
+Every minimal s-t-cut is the boundary of such an S, so the best C - B over all boundaries C that meet B,
+and that leave some s-t-path, is optimal. This takes O(2^k (n + m)) time for k vertices in the component of s,
+and gives up when k exceeds `EXACT_LIMIT`.
+*/
+pub fn exact_set_diversion<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    s: usize,
+    t: usize,
+    set: &[E],
+) -> Result<(W, Vec<E>), SetDiversionError> {
+    let in_set = |e: &E| {
+        set.iter().any(|b| {
+            (b.from(), b.to()) == (e.from(), e.to()) || (b.to(), b.from()) == (e.from(), e.to())
+        })
+    };
+    let reachable = |removed: &dyn Fn(&E) -> bool| {
+        let mut seen = repeat(graph.n(), false);
+        let mut stack = vec![s];
+        seen[s] = true;
+        while let Some(u) = stack.pop() {
+            for e in graph.N(u) {
+                if !seen[e.to()] && !removed(e) {
+                    seen[e.to()] = true;
+                    stack.push(e.to());
+                }
+            }
+        }
+        seen
+    };
+
+    let component = reachable(&|_| false);
+    if s == t || !component[t] {
+        return Err(SetDiversionError::NotOnAnyPath);
+    }
+    let free: Vec<usize> = graph
+        .vertices()
+        .filter(|&u| component[u] && u != s && u != t)
+        .collect();
+    if free.len() > EXACT_LIMIT {
+        return Err(SetDiversionError::TooLargeForExact(free.len() + 2));
+    }
+
+    let mut side = repeat(graph.n(), false);
+    let mut best: Option<(W, Vec<E>)> = None;
+    for mask in 0..1usize << free.len() {
+        side[s] = true;
+        for (i, &u) in free.iter().enumerate() {
+            side[u] = mask >> i & 1 == 1;
+        }
+        let boundary: Vec<E> = graph
+            .edges()
+            .into_iter()
+            .filter(|e| e.from() < e.to() && side[e.from()] != side[e.to()])
+            .collect();
+        if !boundary.iter().any(&in_set) {
+            continue;
+        }
+        let cut: Vec<E> = boundary.into_iter().filter(|e| !in_set(e)).collect();
+        let cost = cut.iter().fold(0.into(), |acc, e| acc + e.weight());
+        if best.as_ref().is_some_and(|(c, _)| *c <= cost) {
+            continue;
+        }
+        let cut_here = |e: &E| side[e.from()] != side[e.to()] && !in_set(e);
+        if reachable(&cut_here)[t] {
+            best = Some((cost, cut));
+        }
+    }
+
+    best.ok_or(SetDiversionError::NotOnAnyPath)
+}
+
+#[cfg(test)]
+mod test_set_diversion {
+    use crate::algorithm::network_diversion::network_diversion;
+    use crate::algorithm::set_diversion::{
+        exact_set_diversion, set_diversion, SetDiversionError, Solver,
+    };
+    use crate::algorithm::test_utility::random_grid;
+    use crate::structure::graph::edge::Edge;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::utility::generator::torus;
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_single_edge_matches_network_diversion() {
+        let planar: PlanarGraph<f64> = std::fs::read_to_string("data/example.in")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let diversion = set_diversion(&planar, 0, 9, &[(7, 8)]).unwrap();
+        assert_eq!(diversion.solver, Solver::Planar);
+        assert_eq!(
+            Some(diversion.cost),
+            network_diversion(&planar, 0, 9, (7, 8)).map(|(cost, _)| cost)
+        );
+
+        // Every path starts with the only edge at 0, so nothing has to be cut.
+        let diversion = set_diversion(&planar, 0, 9, &[(0, 1)]).unwrap();
+        assert_eq!((diversion.cost, diversion.cut.len()), (0.0, 0));
+    }
+
+    #[test]
+    fn test_planar_matches_exact() {
+        let mut random = Random::new(7);
+        for _ in 0..30 {
+            let planar: PlanarGraph<u64> = random_grid(&mut random, 4, 3, 9);
+            let edges: Vec<_> = planar
+                .real()
+                .edges()
+                .into_iter()
+                .filter(|e| e.from() < e.to())
+                .collect();
+            let set: Vec<_> = (0..1 + random.below(3))
+                .map(|_| edges[random.below(edges.len())].clone())
+                .collect();
+            let pairs: Vec<_> = set.iter().map(|e| (e.from(), e.to())).collect();
+
+            let planar_result = set_diversion(&planar, 0, 11, &pairs).map(|d| d.cost);
+            let exact_result = exact_set_diversion(planar.real(), 0, 11, &set).map(|(c, _)| c);
+            assert_eq!(planar_result, exact_result);
+        }
+    }
+
+    #[test]
+    fn test_disconnected_uses_the_dual() {
+        // A triangle, a path, and the isolated vertex 6.
+        let planar: PlanarGraph<u64> =
+            "7 5\n0 0 0\n1 1 0\n2 0 1\n3 5 5\n4 6 5\n5 9 9\n6 9 0\n0 1 1\n1 2 1\n0 2 3\n3 4 1\n4 5 1\n"
+                .parse()
+                .unwrap();
+        let diversion = set_diversion(&planar, 0, 2, &[(1, 2)]).unwrap();
+        assert_eq!(diversion.solver, Solver::Planar);
+        assert_eq!(diversion.cost, 3);
+        assert_eq!(
+            set_diversion(&planar, 0, 4, &[(1, 2)]).err(),
+            Some(SetDiversionError::NotOnAnyPath)
+        );
+    }
+
+    #[test]
+    fn test_torus_falls_back_to_exact() {
+        let (points, rotations) = torus(4, 3);
+        let planar: PlanarGraph<f64> = PlanarGraph::from_rotations(points, rotations).unwrap();
+        let diversion = set_diversion(&planar, 0, 7, &[(0, 1)]).unwrap();
+        assert_eq!(diversion.solver, Solver::Exact);
+        assert_eq!(
+            Some(diversion.cost),
+            network_diversion(&planar, 0, 7, (0, 1)).map(|(cost, _)| cost)
+        );
+    }
+}
@@ -11,7 +11,16 @@ pub fn all_shortest_paths<W: Weight, E: Edge<W>>(
     graph: &UndirectedGraph<W, E>,
     s: usize,
 ) -> Vec<Cost<W>> {
+    shortest_path_tree(graph, s).0
+}
+
+/// Dijkstra from s: the distance to every vertex, and the last edge of a shortest path to it.
+pub fn shortest_path_tree<W: Weight, E: Edge<W>>(
+    graph: &UndirectedGraph<W, E>,
+    s: usize,
+) -> (Vec<Cost<W>>, Vec<Option<E>>) {
     let mut dist = repeat(graph.n(), Infinite);
+    let mut pred = repeat(graph.n(), None);
     let mut done = repeat(graph.n(), false);
     dist[s] = Finite(0.into());
     let mut pqv: BinaryHeap<(Reverse<Order<W>>, usize)> =
@@ -24,12 +33,13 @@ pub fn all_shortest_paths<W: Weight, E: Edge<W>>(
                 let dv = d + e.weight();
                 if Finite(dv) < dist[v] {
                     dist[v] = Finite(dv);
+                    pred[v] = Some(e.clone());
                     pqv.push((Reverse(Order(dv)), v));
                 }
             }
         }
     }
-    dist
+    (dist, pred)
 }
 
 pub fn bfs<W, E>(graph: &UndirectedGraph<W, E>, s: usize) -> Vec<Cost<u64>>
@@ -0,0 +1,92 @@
+use crate::structure::graph::edge::{BasicEdge, Edge};
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::weight::{Weight, Weighted};
+use crate::utility::generator::{grid, Instance};
+use crate::utility::random::Random;
+
+/// A random graph on 7 vertices, with every edge present with probability 1/3 and a weight from 1 to 9.
+pub fn random_graph(random: &mut Random) -> UndirectedGraph<u64, BasicEdge<u64>> {
+    let mut graph = UndirectedGraph::new(7);
+    for u in 0..7 {
+        for v in u + 1..7 {
+            if random.below(3) == 0 {
+                graph.add_edge(BasicEdge::new(u, v, 1 + random.below(9) as u64));
+            }
+        }
+    }
+    graph
+}
+
+/// The cheapest simple s-t-path that is accepted, by extending simple paths from s in every way.
+pub fn brute_force(
+    graph: &UndirectedGraph<u64, BasicEdge<u64>>,
+    s: usize,
+    t: usize,
+    accept: &dyn Fn(&[BasicEdge<u64>]) -> bool,
+) -> Option<u64> {
+    fn extend(
+        graph: &UndirectedGraph<u64, BasicEdge<u64>>,
+        (s, t): (usize, usize),
+        accept: &dyn Fn(&[BasicEdge<u64>]) -> bool,
+        path: &mut Vec<BasicEdge<u64>>,
+        best: &mut Option<u64>,
+    ) {
+        let u = path.last().map_or(s, |e| e.to());
+        if u == t {
+            let cost = path.iter().map(|e| e.weight()).sum::<u64>();
+            if accept(path) && best.is_none_or(|b| cost < b) {
+                *best = Some(cost);
+            }
+            return;
+        }
+        for e in graph.N(u) {
+            if e.to() != s && path.iter().all(|f| f.to() != e.to()) {
+                path.push(e.clone());
+                extend(graph, (s, t), accept, path, best);
+                path.pop();
+            }
+        }
+    }
+    let mut best = None;
+    extend(graph, (s, t), accept, &mut Vec::new(), &mut best);
+    best
+}
+
+/// A `width` x `height` grid with weights from 1 to `heaviest`, in the embedding of its coordinates.
+pub fn random_grid<W: Weight>(
+    random: &mut Random,
+    width: usize,
+    height: usize,
+    heaviest: u32,
+) -> PlanarGraph<W> {
+    weighted_grid(random, width, height, heaviest)
+        .to_string()
+        .parse()
+        .unwrap()
+}
+
+/// A `random_grid`, and one of its edges, drawn uniformly.
+pub fn random_grid_with_edge<W: Weight>(
+    random: &mut Random,
+    width: usize,
+    height: usize,
+    heaviest: u32,
+) -> (PlanarGraph<W>, (usize, usize)) {
+    let instance = weighted_grid(random, width, height, heaviest);
+    let (u, v, _) = instance.edges[random.below(instance.edges.len())];
+    (instance.to_string().parse().unwrap(), (u, v))
+}
+
+fn weighted_grid(random: &mut Random, width: usize, height: usize, heaviest: u32) -> Instance {
+    let mut instance = grid(width, height);
+    instance.randomize_weights(random, 1, heaviest);
+    instance
+}
+
+/// Every side S of an s-t-cut in a graph on n vertices, as whether each vertex is in S, by trying all 2^n subsets.
+pub fn sides(n: usize, s: usize, t: usize) -> impl Iterator<Item = Vec<bool>> {
+    (0..1usize << n)
+        .map(move |mask| (0..n).map(|u| mask >> u & 1 == 1).collect::<Vec<_>>())
+        .filter(move |side| side[s] && !side[t])
+}
@@ -17,7 +17,12 @@ where
         .into_iter()
         .map(|e| if e.from() < e.to() { e } else { e.reverse() })
         .collect();
-    let extra = g.m() - bans.len();
+    // Loops and the banned edges are not subdivided.
+    let extra = g
+        .edges()
+        .iter()
+        .filter(|e| e.from() < e.to() && !bans.contains(*e))
+        .count();
     let old_n = g.n();
     let new_n = g.n() + extra;
     let mut m = g.n();
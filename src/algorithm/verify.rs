@@ -0,0 +1,119 @@
+use crate::algorithm::bottleneck_path::shortest_bottleneck_path;
+use crate::algorithm::network_diversion::{bfs, network_diversion};
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::path_result::PathResult::*;
+use crate::structure::weight::{Weight, Weighted};
+
+pub struct DiversionCheck<W: Weight> {
+    /// The total weight of the (distinct) edges in the proposed cut.
+    pub cost: W,
+    /// An s-t-path that survives the cut without using the diversion edge, if one exists.
+    pub counterexample: Option<Vec<PlanarEdge<W>>>,
+    /// Whether some s-t-path through the diversion edge survives the cut.
+    pub diversion_reachable: bool,
+    /// The optimal cost computed by `network_diversion`, if a diversion exists at all.
+    pub optimum: Option<W>,
+}
+
+impl<W: Weight> DiversionCheck<W> {
+    pub fn is_diversion(&self) -> bool {
+        self.counterexample.is_none() && self.diversion_reachable
+    }
+    pub fn is_optimal(&self) -> bool {
+        self.is_diversion() && self.optimum == Some(self.cost)
+    }
+}
+
+/**
+Problem: Verify Network Diversion
+In: a planar graph G, two vertices s,t in V(G), a diversion edge b in E(G), and a proposed cut X of E(G)
+Out: whether every s-t-path in G - X uses b while some s-t-path in G - X still uses b,
+     together with the cost of X compared to the optimal diversion.
+*/
+pub fn verify_diversion<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    (du, dv): (usize, usize),
+    cut: &[(usize, usize)],
+) -> Result<DiversionCheck<W>, String> {
+    for &u in &[s, t, du, dv] {
+        if u >= planar.n() {
+            return Err(format!("The vertex {} does not exist", u));
+        }
+    }
+    if s == t {
+        return Err("The source and the sink must be different vertices".to_string());
+    }
+    if !planar.real().is_adjacent(du, dv) {
+        return Err(format!(
+            "The diversion edge ({}, {}) does not exist",
+            du, dv
+        ));
+    }
+
+    let mut edges: Vec<PlanarEdge<W>> = Vec::new();
+    for &(u, v) in cut {
+        let e = planar
+            .real()
+            .find_edges(u, v)
+            .into_iter()
+            .next()
+            .ok_or(format!("The edge ({}, {}) does not exist", u, v))?;
+        if !edges.iter().any(|f| f == &e || f == &e.reverse()) {
+            edges.push(e);
+        }
+    }
+    let cost = edges.iter().fold(0.into(), |acc, e| acc + e.weight());
+
+    let mut remaining = planar.real().clone();
+    remaining.delete_edges(&edges);
+
+    let counterexample = bfs(&remaining, s, t, (du, dv)).map(|mut path| {
+        path.reverse();
+        path
+    });
+    let diversion_reachable = match shortest_bottleneck_path(&remaining, s, t, (du, dv)) {
+        Possible { .. } => true,
+        Impossible => false,
+    };
+    let optimum = network_diversion(planar, s, t, (du, dv)).map(|(cost, _)| cost);
+
+    Ok(DiversionCheck {
+        cost,
+        counterexample,
+        diversion_reachable,
+        optimum,
+    })
+}
+
+#[cfg(test)]
+mod test_verify {
+    use crate::algorithm::verify::verify_diversion;
+    use crate::structure::graph::planar_graph::PlanarGraph;
+
+    #[test]
+    fn test_verify_example() {
+        let planar: PlanarGraph<f64> = std::fs::read_to_string("data/example.in")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let b = (7, 8);
+
+        let check = verify_diversion(&planar, 0, 9, b, &[(1, 2), (4, 3), (5, 6)]).unwrap();
+        assert!(check.is_optimal());
+        assert_eq!(check.cost, 3.0);
+
+        let check = verify_diversion(&planar, 0, 9, b, &[(1, 2), (3, 4)]).unwrap();
+        assert!(check.counterexample.is_some());
+        assert!(check.diversion_reachable);
+
+        let check = verify_diversion(&planar, 0, 9, b, &[(0, 1), (3, 4), (5, 6)]).unwrap();
+        assert!(check.counterexample.is_none());
+        assert!(!check.diversion_reachable);
+
+        assert!(verify_diversion(&planar, 0, 9, b, &[(0, 9)]).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::algorithm::bottleneck_path::shortest_bottleneck_path;
+use crate::algorithm::network_diversion::bfs;
+use crate::algorithm::parity_path::{shortest_path_with_parity, Parity};
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::graph::undirected_graph::UndirectedGraph;
+use crate::structure::path_result::PathResult::*;
+use crate::structure::weight::Weight;
+use crate::utility::misc::debug;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum VertexDiversionError {
+    CheckpointIsTerminal,
+    NotOnAnyPath,
+    /// The embedding has this genus, so the curves around the checkpoint need not be cuts.
+    NotPlanar(usize),
+}
+
+impl Display for VertexDiversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VertexDiversionError::CheckpointIsTerminal => {
+                write!(
+                    f,
+                    "The checkpoint is s or t, so every s-t-path visits it already"
+                )
+            }
+            VertexDiversionError::NotOnAnyPath => {
+                write!(
+                    f,
+                    "The checkpoint is not on any s-t-path, so no diversion exists"
+                )
+            }
+            VertexDiversionError::NotPlanar(genus) => {
+                write!(
+                    f,
+                    "The embedding has genus {}, but vertex diversion needs a plane embedding",
+                    genus
+                )
+            }
+        }
+    }
+}
+
+/**
+Problem: Planar Vertex Diversion
+In: a planar graph G, two vertices s,t in V(G), and a 'checkpoint' vertex v in V(G) - {s,t}
+Out: a minimum-cost set of edges X such that every s-t-path in G - X visits v,
+     while some s-t-path in G - X still exists.
+
+The cut corresponds to a closed curve through v and the faces of G that separates s from t.
+Removing v merges all the faces around it, so we connect two new dual vertices a and b to all these faces,
+and look for the shortest a-b-path in the dual of G - v that crosses an s-t-path of G - v an odd number of times.
+The edges incident to v are never cut, since the curve can pass through v for free.
+*/
+pub fn vertex_diversion<W: Weight>(
+    planar: &PlanarGraph<W>,
+    s: usize,
+    t: usize,
+    v: usize,
+) -> Result<(W, Vec<PlanarEdge<W>>), VertexDiversionError> {
+    if v == s || v == t {
+        return Err(VertexDiversionError::CheckpointIsTerminal);
+    }
+    if !planar.is_planar() {
+        return Err(VertexDiversionError::NotPlanar(planar.genus()));
+    }
+
+    let mut without = planar.real().clone();
+    without.delete_edges(planar.real().N(v));
+    // There is no edge (v, v), so this is a plain search in G - v.
+    let path = match bfs(&without, s, t, (v, v)) {
+        Some(path) => path,
+        None => {
+            debug(format!(
+                "Every {}-{}-path already visits {}, no diversion is needed.",
+                s, t, v
+            ));
+            return if on_some_path(planar.real(), s, t, v) {
+                Ok((0.into(), Vec::new()))
+            } else {
+                Err(VertexDiversionError::NotOnAnyPath)
+            };
+        }
+    };
+
+    let f = planar.f();
+    let (a, b) = (f, f + 1);
+    let around: Vec<PlanarEdge<W>> = planar.real().N(v).clone();
+    let mut dual = UndirectedGraph::new(f + 2);
+    for e in planar.dual().edges() {
+        let crosses_v = e.left() == v || e.right() == v;
+        if e.from() < e.to() && !crosses_v {
+            dual.add_edge(e);
+        }
+    }
+    for e in &around {
+        for (x, face) in [(a, e.left()), (b, e.left())] {
+            let mut link = e.rotate_right();
+            link.from = x;
+            link.to = face;
+            link.weight = 0.into();
+            dual.add_edge(link);
+        }
+    }
+
+    // Bridges of G are loops in the dual, which no simple path can use.
+    let marked: Vec<PlanarEdge<W>> = path
+        .iter()
+        .map(|e| e.rotate_right())
+        .filter(|e| e.from() != e.to())
+        .collect();
+    match shortest_path_with_parity(&dual, a, b, &marked, Parity::Odd) {
+        Impossible => {
+            debug(format!(
+                "No vertex diversion exists, no {}-{}-paths visit {}.",
+                s, t, v
+            ));
+            Err(VertexDiversionError::NotOnAnyPath)
+        }
+        Possible { cost, path } => {
+            let cut: Vec<PlanarEdge<W>> = path
+                .iter()
+                .filter(|e| e.from() < f && e.to() < f)
+                .map(|e| e.rotate_right())
+                .collect();
+            debug(format!(
+                "We have to cut {} edges to force all {}-{}-paths through {}, with a total cost of {}.",
+                cut.len(),
+                s,
+                t,
+                v,
+                cost
+            ));
+            Ok((cost, cut))
+        }
+    }
+}
+
+/// Whether some simple s-t-path visits v, that is, some simple path uses an edge incident to v.
+fn on_some_path<W: Weight>(
+    graph: &UndirectedGraph<W, PlanarEdge<W>>,
+    s: usize,
+    t: usize,
+    v: usize,
+) -> bool {
+    graph.N(v).iter().any(|e| {
+        matches!(
+            shortest_bottleneck_path(graph, s, t, (e.from(), e.to())),
+            Possible { .. }
+        )
+    })
+}
+
+#[cfg(test)]
+mod test_vertex_diversion {
+    use crate::algorithm::network_diversion::bfs;
+    use crate::algorithm::vertex_diversion::{vertex_diversion, VertexDiversionError};
+    use crate::structure::graph::planar_graph::PlanarGraph;
+
+    fn example() -> PlanarGraph<f64> {
+        std::fs::read_to_string("data/example.in")
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_vertex_diversion_example() {
+        let planar = example();
+        let (cost, cut) = vertex_diversion(&planar, 0, 9, 5).unwrap();
+        assert_eq!(cost, 2.0);
+
+        let mut diverted = planar.real().clone();
+        diverted.delete_edges(&cut);
+        diverted.delete_edges(planar.real().N(5));
+        assert!(bfs(&diverted, 0, 9, (5, 5)).is_none());
+
+        let (cost, _) = vertex_diversion(&planar, 0, 9, 7).unwrap();
+        assert_eq!(cost, 3.0);
+        let (cost, cut) = vertex_diversion(&planar, 0, 9, 1).unwrap();
+        assert_eq!((cost, cut.len()), (0.0, 0));
+    }
+
+    #[test]
+    fn test_vertex_diversion_errors() {
+        let planar = example();
+        assert_eq!(
+            vertex_diversion(&planar, 0, 9, 0),
+            Err(VertexDiversionError::CheckpointIsTerminal)
+        );
+        assert_eq!(
+            vertex_diversion(&planar, 1, 9, 0),
+            Err(VertexDiversionError::NotOnAnyPath)
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use planar_network_diversion::algorithm::exact_diversion::exact_network_diversion;
+use planar_network_diversion::structure::graph::edge::Edge;
+
+use crate::command::{parse_edge_list, usage, vertex};
+
+pub fn run(args: &[String]) {
+    if args.len() != 5 {
+        usage("exact-diversion <file_name> <s> <t> <b1> <b2>");
+    }
+    let graph = parse_edge_list(&args[0]);
+    let s = vertex(&graph, &args[1], "s");
+    let t = vertex(&graph, &args[2], "t");
+    let b1 = vertex(&graph, &args[3], "b1");
+    let b2 = vertex(&graph, &args[4], "b2");
+    if !graph.is_adjacent(b1, b2) {
+        usage(&format!("({}, {}) must be an edge of the graph", b1, b2));
+    }
+
+    match exact_network_diversion(&graph, s, t, (b1, b2), &|_| false) {
+        Ok((cost, cut)) => {
+            println!("{}", cost);
+            for e in cut {
+                println!("{},{}", e.from(), e.to());
+            }
+        }
+        Err(err) => eprintln!("WARN: {}", err),
+    }
+}
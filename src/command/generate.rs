@@ -0,0 +1,77 @@
+use planar_network_diversion::utility::generator::{
+    clustered_points, cylinder, delaunay, grid, random_maximal_planar, uniform_points, wheel,
+};
+use planar_network_diversion::utility::random::Random;
+use std::io::{stdout, Write};
+
+use crate::command::{parse_arg, take_flag, usage};
+
+const USAGE: &str = "generate <family> <args..> [--seed <seed>] [--weights <lo> <hi>]
+Families:
+    grid <width> <height>
+    wheel <spokes>
+    cylinder <rings> <spokes>
+    delaunay <n>
+    clustered <n> <clusters>
+    maximal <n>";
+
+pub fn run(args: &[String]) {
+    let mut args = args.to_vec();
+    let seed: u64 = take_flag(&mut args, "--seed", 1).map_or(0, |v| parse_arg(&v[0], "seed"));
+    let weights = take_flag(&mut args, "--weights", 2);
+    let mut random = Random::new(seed);
+
+    let arg = |i: usize, name: &str| -> usize {
+        parse_arg(args.get(i).unwrap_or_else(|| usage(USAGE)), name)
+    };
+    let expect_args = |k: usize| {
+        if args.len() != k + 1 {
+            usage(USAGE);
+        }
+    };
+
+    let mut instance = match args.first().map(String::as_str) {
+        Some("grid") => {
+            expect_args(2);
+            grid(arg(1, "width"), arg(2, "height"))
+        }
+        Some("wheel") => {
+            expect_args(1);
+            wheel(arg(1, "spokes")).unwrap_or_else(|err| usage(err))
+        }
+        Some("cylinder") => {
+            expect_args(2);
+            cylinder(arg(1, "rings"), arg(2, "spokes")).unwrap_or_else(|err| usage(err))
+        }
+        Some("delaunay") => {
+            expect_args(1);
+            delaunay(uniform_points(&mut random, arg(1, "n")))
+        }
+        Some("clustered") => {
+            expect_args(2);
+            delaunay(clustered_points(
+                &mut random,
+                arg(1, "n"),
+                arg(2, "clusters"),
+            ))
+        }
+        Some("maximal") => {
+            expect_args(1);
+            random_maximal_planar(&mut random, arg(1, "n")).unwrap_or_else(|err| usage(err))
+        }
+        _ => usage(USAGE),
+    };
+
+    if let Some(ws) = weights {
+        let lo: u32 = parse_arg(&ws[0], "lo");
+        let hi: u32 = parse_arg(&ws[1], "hi");
+        if lo > hi {
+            usage(USAGE);
+        }
+        instance.randomize_weights(&mut random, lo, hi);
+    }
+    // Stop quietly if the reading end of a pipe has been closed, e.g. by `head`.
+    if write!(stdout().lock(), "{}", instance).is_err() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,86 @@
+pub mod exact;
+pub mod generate;
+pub mod path;
+pub mod solve;
+pub mod verify;
+
+use planar_network_diversion::structure::graph::edge::BasicEdge;
+use planar_network_diversion::structure::graph::planar_graph::PlanarGraph;
+use planar_network_diversion::structure::graph::undirected_graph::UndirectedGraph;
+use std::fs::read_to_string;
+use std::io::{stdin, Read};
+use std::str::FromStr;
+
+/// Read the whole file, where `-` means standard input.
+pub fn read_input(filename: &str) -> String {
+    if filename == "-" {
+        let mut buffer = String::new();
+        stdin()
+            .read_to_string(&mut buffer)
+            .expect("Could not read from stdin");
+        buffer
+    } else {
+        read_to_string(filename).unwrap_or_else(|_| panic!("Could not find {}", filename))
+    }
+}
+
+pub fn parse_graph(filename: &str) -> PlanarGraph<f64> {
+    let graph: PlanarGraph<f64> = read_input(filename)
+        .parse()
+        .expect("Could not read the graph");
+    graph
+}
+
+pub type EdgeList = UndirectedGraph<f64, BasicEdge<f64>>;
+
+/// Plain edge lists: first `n`, then one line `u v weight` per edge.
+pub fn parse_edge_list(filename: &str) -> EdgeList {
+    read_input(filename)
+        .parse()
+        .unwrap_or_else(|err| panic!("Could not read the graph: {}", err))
+}
+
+pub fn vertex(graph: &EdgeList, arg: &str, name: &str) -> usize {
+    let u: usize = parse_arg(arg, name);
+    if u >= graph.n() {
+        usage(&format!("{} must be a vertex in 0..{}", name, graph.n()));
+    }
+    u
+}
+
+pub fn usage(msg: &str) -> ! {
+    eprintln!("Usage: {}", msg);
+    std::process::exit(1);
+}
+
+pub fn parse_arg<T: FromStr>(arg: &str, name: &str) -> T {
+    arg.parse()
+        .unwrap_or_else(|_| panic!("{} must be a number, but got '{}'", name, arg))
+}
+
+fn numbers(line: &str) -> Vec<usize> {
+    line.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|w| !w.is_empty())
+        .map(|w| parse_arg(w, "Every vertex id"))
+        .collect()
+}
+
+/// The non-empty lines of the file as vertex ids, separated by whitespace or commas.
+pub fn rows(filename: &str) -> Vec<Vec<usize>> {
+    read_input(filename)
+        .lines()
+        .map(str::trim)
+        .filter(|&l| !l.is_empty() && !l.starts_with('%'))
+        .map(numbers)
+        .collect()
+}
+
+/// Remove `--name` and the `count` values following it from the arguments, if present.
+pub fn take_flag(args: &mut Vec<String>, name: &str, count: usize) -> Option<Vec<String>> {
+    let i = args.iter().position(|a| a == name)?;
+    if i + count >= args.len() {
+        usage(&format!("{} expects {} value(s)", name, count));
+    }
+    let values = args.drain(i..=i + count).skip(1).collect();
+    Some(values)
+}
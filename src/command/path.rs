@@ -0,0 +1,78 @@
+use planar_network_diversion::algorithm::bottleneck_path::{
+    shortest_bottleneck_path, shortest_directed_bottleneck_path, shortest_vertex_bottleneck_path,
+};
+use planar_network_diversion::algorithm::odd_path::{shortest_even_path, shortest_odd_path};
+use planar_network_diversion::structure::graph::edge::{BasicEdge, Edge};
+use planar_network_diversion::structure::path_result::{PathResult, PathResult::*};
+
+use crate::command::{parse_edge_list, take_flag, usage, vertex};
+
+fn print_path(result: PathResult<f64, BasicEdge<f64>>, what: &str) {
+    match result {
+        Possible { cost, path } => {
+            println!("{}", cost);
+            for e in path {
+                println!("{},{}", e.from(), e.to());
+            }
+        }
+        Impossible => eprintln!("WARN: No {} found", what),
+    }
+}
+
+pub fn run_odd_path(args: &[String]) {
+    if args.len() != 3 {
+        usage("odd-path <file_name> <s> <t>");
+    }
+    let graph = parse_edge_list(&args[0]);
+    let s = vertex(&graph, &args[1], "s");
+    let t = vertex(&graph, &args[2], "t");
+
+    print_path(shortest_odd_path(&graph, s, t), "odd path");
+}
+
+pub fn run_even_path(args: &[String]) {
+    if args.len() != 3 {
+        usage("even-path <file_name> <s> <t>");
+    }
+    let graph = parse_edge_list(&args[0]);
+    let s = vertex(&graph, &args[1], "s");
+    let t = vertex(&graph, &args[2], "t");
+
+    print_path(shortest_even_path(&graph, s, t), "even path");
+}
+
+pub fn run_bottleneck_path(args: &[String]) {
+    let mut args = args.to_vec();
+    let directed = take_flag(&mut args, "--directed", 0).is_some();
+    if args.len() != 5 && (args.len() != 4 || directed) {
+        usage("bottleneck-path <file_name> <s> <t> (<x> | <b1> <b2> [--directed])");
+    }
+    let graph = parse_edge_list(&args[0]);
+    let s = vertex(&graph, &args[1], "s");
+    let t = vertex(&graph, &args[2], "t");
+    if args.len() == 4 {
+        let x = vertex(&graph, &args[3], "x");
+        print_path(
+            shortest_vertex_bottleneck_path(&graph, s, t, x),
+            "bottleneck path",
+        );
+        return;
+    }
+    let b1 = vertex(&graph, &args[3], "b1");
+    let b2 = vertex(&graph, &args[4], "b2");
+    if !graph.is_adjacent(b1, b2) {
+        usage(&format!("({}, {}) must be an edge of the graph", b1, b2));
+    }
+
+    if directed {
+        match shortest_directed_bottleneck_path(&graph, s, t, (b1, b2)) {
+            Ok(result) => print_path(result, "bottleneck path from b1 to b2"),
+            Err(err) => eprintln!("WARN: {}", err),
+        }
+    } else {
+        print_path(
+            shortest_bottleneck_path(&graph, s, t, (b1, b2)),
+            "bottleneck path",
+        );
+    }
+}
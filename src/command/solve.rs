@@ -0,0 +1,413 @@
+use planar_network_diversion::algorithm::directed_diversion::directed_network_diversion;
+use planar_network_diversion::algorithm::intersection_diversion::intersection_diversion;
+use planar_network_diversion::algorithm::k_best_diversion::k_best_diversions;
+use planar_network_diversion::algorithm::lexicographic_diversion::{
+    lexicographic_diversion, TieBreak,
+};
+use planar_network_diversion::algorithm::multi_terminal_diversion::multi_terminal_diversion;
+use planar_network_diversion::algorithm::network_diversion::protected_diversion;
+use planar_network_diversion::algorithm::optimal_diversion::optimal_diversions;
+use planar_network_diversion::algorithm::pareto_diversion::{
+    cheapest_with_closures, pareto_diversions,
+};
+use planar_network_diversion::structure::graph::directed_planar_graph::DirectedPlanarGraph;
+use planar_network_diversion::structure::graph::edge::Edge;
+use planar_network_diversion::structure::graph::planar_edge::PlanarEdge;
+use planar_network_diversion::structure::graph::planar_graph::PlanarGraph;
+use planar_network_diversion::structure::graph::point::Point;
+use planar_network_diversion::structure::graph::spatial_index::SpatialIndex;
+use std::time::Instant;
+
+use crate::command::{parse_arg, parse_graph, read_input, rows, take_flag, usage};
+
+const USAGE: &str = "solve <file_name> <s> <t> (<b1> <b2> | <bx,by>) [--protected <edge_file>] [--directed] [--alternatives <k> | --all-optimal <limit> | --pareto <limit> | --max-closures <k>] [--tie-break (edges|length)] [--vertex-costs <cost_file>]
+The vertices s and t can also be given as coordinates `x,y`, which are snapped to the nearest vertex.
+Several sources or sinks are joined with `+`, as in `0+3`, and then every path from any source to any sink has to use b.
+The diversion edge can be given as a single point `x,y`, which is snapped to the nearest edge.
+The edges in the edge file, one `u v` (or `u,v`) per line, are never cut.
+With --directed, every edge line `u v weight` of the graph is the one-way arc u -> v.
+With --alternatives, the k cheapest minimal diversion sets are printed, each after a line `% cost`.
+With --all-optimal, up to limit optimal diversion sets are printed the same way, and standard error
+reports whether the optimum is unique, and which edges are in every and in some optimal set.
+With --pareto, one diversion set per point of the Pareto front of cost and number of closed edges is printed
+the same way, searching at most limit diversion sets between two bends of the front,
+and with --max-closures, the cheapest diversion set that closes at most k edges.
+With --tie-break, the cheapest diversion set with the fewest edges, or with the shortest total length, is printed.
+With --vertex-costs, the vertices in the cost file, one `v cost` per line, can be closed for that cost, and the closed
+vertices are printed after the closed edges, after a line `% vertices`.";
+
+/// Coordinates are written as `x,y`, while vertex ids never contain a comma.
+fn parse_point(arg: &str) -> Option<Point> {
+    let (x, y) = arg.split_once(',')?;
+    Some(Point::new(
+        parse_arg(x.trim(), "x coordinate"),
+        parse_arg(y.trim(), "y coordinate"),
+    ))
+}
+
+fn snap_vertex(index: Option<&SpatialIndex>, arg: &str, name: &str) -> usize {
+    match parse_point(arg) {
+        None => parse_arg(arg, name),
+        Some(p) => {
+            let (u, d) = index
+                .expect("The index is built for every coordinate")
+                .nearest_vertex(&p)
+                .unwrap_or_else(|| usage("the graph has no vertices to snap to"));
+            eprintln!(
+                "{} = {} (snapped from ({}, {}), distance {})",
+                name, u, p.x, p.y, d
+            );
+            u
+        }
+    }
+}
+
+fn snap_edge(index: Option<&SpatialIndex>, arg: &str) -> (usize, usize) {
+    let p = parse_point(arg).unwrap_or_else(|| usage(USAGE));
+    let (e, d) = index
+        .expect("The index is built for every coordinate")
+        .nearest_edge(&p)
+        .unwrap_or_else(|| usage("the graph has no edges to snap to"));
+    eprintln!(
+        "b = ({}, {}) (snapped from ({}, {}), distance {})",
+        e.0, e.1, p.x, p.y, d
+    );
+    e
+}
+
+/// The sources S and the sinks T, and the diversion edge, snapped to the graph when they are given as coordinates.
+/// The spatial index puts long edges into many cells, so it is only built when some argument is a coordinate.
+fn query(graph: &PlanarGraph<f64>, args: &[String]) -> (Vec<usize>, Vec<usize>, (usize, usize)) {
+    let index = args[1..4]
+        .iter()
+        .any(|arg| arg.contains(','))
+        .then(|| graph.spatial_index());
+    let index = index.as_ref();
+    let s = args[1]
+        .split('+')
+        .map(|arg| snap_vertex(index, arg, "s"))
+        .collect();
+    let t = args[2]
+        .split('+')
+        .map(|arg| snap_vertex(index, arg, "t"))
+        .collect();
+    let b = if args.len() == 5 {
+        (parse_arg(&args[3], "b1"), parse_arg(&args[4], "b2"))
+    } else {
+        snap_edge(index, &args[3])
+    };
+    (s, t, b)
+}
+
+/// The only vertex of S or T, for the modes that do not take several sources or sinks.
+fn single(terminals: &[usize]) -> usize {
+    match terminals {
+        &[u] => u,
+        _ => usage("several sources or sinks can not be combined with --directed, --protected, --tie-break or the modes that print several diversion sets"),
+    }
+}
+
+/// Only the plain query falls back to the exact search when the embedding is not planar.
+fn planar_only(graph: &PlanarGraph<f64>, option: &str) {
+    if !graph.is_planar() {
+        usage(&format!(
+            "{} needs a plane embedding, but this one has genus {}",
+            option,
+            graph.genus()
+        ));
+    }
+}
+
+/// Every diversion set after a line `% cost`, so the file can still be read with `rows`.
+fn print_sets(sets: &[(f64, Vec<PlanarEdge<f64>>)]) {
+    for (cost, cut) in sets {
+        println!("% {}", cost);
+        for e in cut {
+            println!("{},{}", e.from(), e.to());
+        }
+    }
+}
+
+/// The closed edges and vertices when intersections can be closed, with the costs from the cost file.
+fn close_intersections(
+    graph: &PlanarGraph<f64>,
+    s: usize,
+    t: usize,
+    b: (usize, usize),
+    file: &str,
+) {
+    let mut costs = vec![None; graph.n()];
+    for line in read_input(file).lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+        match line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            &[v, cost] => {
+                let v: usize = parse_arg(v, "Every vertex id");
+                if v >= graph.n() {
+                    usage(&format!("vertex {} is not in the graph", v));
+                }
+                costs[v] = Some(parse_arg(cost, "Every vertex cost"));
+            }
+            _ => usage(USAGE),
+        }
+    }
+
+    let start_time = Instant::now();
+    let result = intersection_diversion(graph, s, t, b, &costs);
+    let secs = start_time.elapsed().as_secs_f64();
+    match result {
+        Ok(closed) => {
+            if closed.exact {
+                eprintln!("{:.3} s", secs);
+            } else {
+                eprintln!(
+                    "{:.3} s: WARN: no closure of intersections was found in time, so only roads are closed",
+                    secs
+                );
+            }
+            for e in closed.edges {
+                println!("{},{}", e.from(), e.to());
+            }
+            println!("% vertices");
+            for v in closed.vertices {
+                println!("{}", v);
+            }
+        }
+        Err(err) => eprintln!("{:.3} s: WARN: {}", secs, err),
+    }
+}
+
+/// The modes that print several diversion sets instead of a single one.
+enum Enumeration {
+    Alternatives(usize),
+    AllOptimal(usize),
+    Pareto(usize),
+    MaxClosures(usize),
+}
+
+/// The cheapest diversion sets are enumerated one by one, and --max-closures stops after this many.
+const ENUMERATION_LIMIT: usize = 1000;
+
+fn enumerate(graph: &PlanarGraph<f64>, s: usize, t: usize, b: (usize, usize), mode: Enumeration) {
+    let start_time = Instant::now();
+    match mode {
+        Enumeration::Alternatives(k) => {
+            let result = k_best_diversions(graph, s, t, b, k);
+            let secs = start_time.elapsed().as_secs_f64();
+            match result {
+                Ok(found) => {
+                    eprintln!("{:.3} s: {} of {} alternatives found", secs, found.len(), k);
+                    print_sets(&found);
+                }
+                Err(err) => eprintln!("{:.3} s: WARN: {}", secs, err),
+            }
+        }
+        Enumeration::AllOptimal(limit) => {
+            let optimal = match optimal_diversions(graph, s, t, b, limit) {
+                Ok(optimal) => optimal,
+                Err(err) => {
+                    let secs = start_time.elapsed().as_secs_f64();
+                    eprintln!("{:.3} s: WARN: {}", secs, err);
+                    return;
+                }
+            };
+            let secs = start_time.elapsed().as_secs_f64();
+            if optimal.is_unique() {
+                eprintln!("{:.3} s: the optimal diversion set is unique", secs);
+            } else if optimal.complete {
+                eprintln!(
+                    "{:.3} s: {} optimal diversion sets",
+                    secs,
+                    optimal.sets.len()
+                );
+            } else {
+                eprintln!(
+                    "{:.3} s: WARN: more than {} optimal diversion sets, the edges in some optimal set may be incomplete",
+                    secs,
+                    optimal.sets.len()
+                );
+            }
+            let pairs = |edges: &[PlanarEdge<f64>]| {
+                edges
+                    .iter()
+                    .map(|e| format!("{},{}", e.from(), e.to()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            eprintln!("in every optimal set: {}", pairs(&optimal.in_every));
+            eprintln!("in some optimal set: {}", pairs(&optimal.in_some));
+            let found: Vec<_> = optimal
+                .sets
+                .into_iter()
+                .map(|cut| (optimal.cost, cut))
+                .collect();
+            print_sets(&found);
+        }
+        Enumeration::Pareto(limit) => {
+            let front = match pareto_diversions(graph, s, t, b, limit) {
+                Ok(front) => front,
+                Err(err) => {
+                    let secs = start_time.elapsed().as_secs_f64();
+                    eprintln!("{:.3} s: WARN: {}", secs, err);
+                    return;
+                }
+            };
+            let secs = start_time.elapsed().as_secs_f64();
+            if front.complete {
+                eprintln!(
+                    "{:.3} s: {} points on the Pareto front",
+                    secs,
+                    front.points.len()
+                );
+            } else {
+                eprintln!(
+                    "{:.3} s: WARN: the Pareto front may miss points, after {} diversion sets between two of its points",
+                    secs, limit
+                );
+            }
+            print_sets(&front.points);
+        }
+        Enumeration::MaxClosures(k) => {
+            let result = cheapest_with_closures(graph, s, t, b, k, ENUMERATION_LIMIT);
+            let secs = start_time.elapsed().as_secs_f64();
+            match result {
+                Ok(found) => {
+                    eprintln!("{:.3} s", secs);
+                    print_sets(&[found]);
+                }
+                Err(err) => eprintln!("{:.3} s: WARN: {}", secs, err),
+            }
+        }
+    }
+}
+
+pub fn run(args: &[String]) {
+    let mut args = args.to_vec();
+    let directed = take_flag(&mut args, "--directed", 0).is_some();
+    let protected_file = take_flag(&mut args, "--protected", 1).map(|values| values[0].clone());
+    let mut modes = Vec::new();
+    if let Some(values) = take_flag(&mut args, "--alternatives", 1) {
+        modes.push(Enumeration::Alternatives(parse_arg(&values[0], "k")));
+    }
+    if let Some(values) = take_flag(&mut args, "--all-optimal", 1) {
+        modes.push(Enumeration::AllOptimal(parse_arg(&values[0], "limit")));
+    }
+    if let Some(values) = take_flag(&mut args, "--pareto", 1) {
+        modes.push(Enumeration::Pareto(parse_arg(&values[0], "limit")));
+    }
+    if let Some(values) = take_flag(&mut args, "--max-closures", 1) {
+        modes.push(Enumeration::MaxClosures(parse_arg(&values[0], "k")));
+    }
+    let vertex_costs = take_flag(&mut args, "--vertex-costs", 1).map(|values| values[0].clone());
+    let tie_break = take_flag(&mut args, "--tie-break", 1).map(|values| match values[0].as_str() {
+        "edges" => TieBreak::FewestEdges,
+        "length" => TieBreak::ShortestLength,
+        _ => usage(USAGE),
+    });
+    if args.len() != 4 && args.len() != 5 {
+        usage(USAGE);
+    }
+    let from_stdin = [
+        Some(&args[0]),
+        protected_file.as_ref(),
+        vertex_costs.as_ref(),
+    ]
+    .iter()
+    .filter(|file| file.is_some_and(|file| file == "-"))
+    .count();
+    if from_stdin > 1 {
+        usage("at most one of the inputs can be read from stdin");
+    }
+    let protected: Vec<(usize, usize)> = protected_file
+        .map(|file| {
+            rows(&file)
+                .into_iter()
+                .map(|row| match row.as_slice() {
+                    &[u, v] => (u, v),
+                    _ => usage(USAGE),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if let Some(file) = vertex_costs {
+        if !modes.is_empty() || directed || !protected.is_empty() || tie_break.is_some() {
+            usage("--vertex-costs can not be combined with the other options");
+        }
+        let graph = parse_graph(&args[0]);
+        planar_only(&graph, "--vertex-costs");
+        let (s, t, b) = query(&graph, &args);
+        close_intersections(&graph, single(&s), single(&t), b, &file);
+        return;
+    }
+
+    if let Some(mode) = modes.pop() {
+        if !modes.is_empty() || directed || !protected.is_empty() || tie_break.is_some() {
+            usage("--alternatives, --all-optimal, --pareto and --max-closures can not be combined with each other, or with --directed, --protected or --tie-break");
+        }
+        let graph = parse_graph(&args[0]);
+        planar_only(
+            &graph,
+            "--alternatives, --all-optimal, --pareto and --max-closures",
+        );
+        let (s, t, b) = query(&graph, &args);
+        enumerate(&graph, single(&s), single(&t), b, mode);
+        return;
+    }
+
+    let start_time;
+    let result = if directed {
+        if !protected.is_empty() || tie_break.is_some() {
+            usage("--protected and --tie-break can not be combined with --directed");
+        }
+        let graph: DirectedPlanarGraph<f64> = read_input(&args[0])
+            .parse()
+            .unwrap_or_else(|err| panic!("Could not read the graph: {}", err));
+        planar_only(graph.planar(), "--directed");
+        let (s, t, (b1, b2)) = query(graph.planar(), &args);
+        // A snapped two-way street is diverted in the direction of its first arc.
+        let b = if graph.real().has_arc(b1, b2) {
+            (b1, b2)
+        } else {
+            (b2, b1)
+        };
+        start_time = Instant::now();
+        directed_network_diversion(&graph, single(&s), single(&t), b).map_err(|err| err.to_string())
+    } else {
+        let graph = parse_graph(&args[0]);
+        let (s, t, b) = query(&graph, &args);
+        start_time = Instant::now();
+        match tie_break {
+            Some(_) if !protected.is_empty() => {
+                usage("--tie-break can not be combined with --protected")
+            }
+            Some(tie_break) => {
+                planar_only(&graph, "--tie-break");
+                lexicographic_diversion(&graph, single(&s), single(&t), b, tie_break)
+                    .map_err(|err| err.to_string())
+            }
+            None if protected.is_empty() && (s.len() > 1 || t.len() > 1) => {
+                multi_terminal_diversion(&graph, &s, &t, b).map_err(|err| err.to_string())
+            }
+            None => protected_diversion(&graph, single(&s), single(&t), b, &protected)
+                .map_err(|err| err.to_string()),
+        }
+    };
+
+    let secs = start_time.elapsed().as_secs_f64();
+    match result {
+        Ok((_, v)) => {
+            eprintln!("{:.3} s", secs);
+            for e in v {
+                println!("{},{}", e.from(), e.to());
+            }
+        }
+        Err(err) => eprintln!("{:.3} s: WARN: {}", secs, err),
+    }
+}
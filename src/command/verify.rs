@@ -0,0 +1,72 @@
+use planar_network_diversion::algorithm::verify::verify_diversion;
+use planar_network_diversion::structure::graph::edge::Edge;
+
+use crate::command::{parse_graph, rows, usage};
+
+const USAGE: &str = "verify <file_name> <query_file> <cut_file>
+The query file contains the line `s t b1 b2`, and the cut file contains one edge `u v` (or `u,v`) per line.";
+
+pub fn run(args: &[String]) {
+    if args.len() != 3 {
+        usage(USAGE);
+    }
+    if args.iter().filter(|&a| a == "-").count() > 1 {
+        usage("at most one of the inputs can be read from stdin");
+    }
+    let graph = parse_graph(&args[0]);
+
+    let query = rows(&args[1]);
+    let (s, t, b) = match query.first().map(Vec::as_slice) {
+        Some(&[s, t, b1, b2]) => (s, t, (b1, b2)),
+        _ => usage(USAGE),
+    };
+    let cut: Vec<(usize, usize)> = rows(&args[2])
+        .into_iter()
+        .map(|row| match row.as_slice() {
+            &[u, v] => (u, v),
+            _ => usage(USAGE),
+        })
+        .collect();
+
+    let check = verify_diversion(&graph, s, t, b, &cut).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+
+    println!("cost: {}", check.cost);
+    match &check.counterexample {
+        None => println!("every {}-{}-path uses ({}, {}): yes", s, t, b.0, b.1),
+        Some(path) => {
+            println!("every {}-{}-path uses ({}, {}): no", s, t, b.0, b.1);
+            let walk: Vec<String> = std::iter::once(s)
+                .chain(path.iter().map(Edge::to))
+                .map(|u| u.to_string())
+                .collect();
+            println!("counterexample: {}", walk.join(" -> "));
+        }
+    }
+    println!(
+        "({}, {}) is on some {}-{}-path: {}",
+        b.0,
+        b.1,
+        s,
+        t,
+        if check.diversion_reachable {
+            "yes"
+        } else {
+            "no"
+        }
+    );
+    match check.optimum {
+        None => println!("optimal: no diversion exists"),
+        Some(opt) if check.is_optimal() => println!("optimal: yes (optimum {})", opt),
+        Some(opt) if check.is_diversion() && check.cost < opt => {
+            println!("optimal: WARN: cheaper than the computed optimum {}", opt)
+        }
+        Some(opt) => println!("optimal: no (optimum {})", opt),
+    }
+
+    if !check.is_diversion() {
+        std::process::exit(2);
+    }
+}
@@ -1,50 +1,35 @@
-use std::fs::read_to_string;
-
 pub mod algorithm;
+mod command;
 pub mod structure;
 pub mod utility;
 
-use planar_network_diversion::algorithm::network_diversion::network_diversion;
-use planar_network_diversion::structure::graph::planar_graph::PlanarGraph;
-
 use std::env;
-use std::time::Instant;
 
-use planar_network_diversion::structure::graph::edge::Edge;
-
-fn parse_graph(filename: &str) -> PlanarGraph<f64> {
-    let graph: PlanarGraph<f64> = read_to_string(filename)
-        .expect("Could not find the graph")
-        .parse()
-        .expect("Could not read the graph");
-    graph
-}
+const USAGE: &str = "Usage:
+    {0} <file_name> <s> <t> (<b1> <b2> | <bx,by>)
+    {0} solve <file_name> <s> <t> (<b1> <b2> | <bx,by>) [--protected <edge_file>] [--directed] [--alternatives <k> | --all-optimal <limit> | --pareto <limit> | --max-closures <k>] [--tie-break (edges|length)] [--vertex-costs <cost_file>]
+    {0} generate <family> <args..> [--seed <seed>] [--weights <lo> <hi>]
+    {0} verify <file_name> <query_file> <cut_file>
+    {0} odd-path <edge_list> <s> <t>
+    {0} even-path <edge_list> <s> <t>
+    {0} bottleneck-path <edge_list> <s> <t> (<x> | <b1> <b2> [--directed])
+    {0} exact-diversion <edge_list> <s> <t> <b1> <b2>";
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() != 6 {
-        eprintln!("Usage: {} <file_name> <s> <t> <b1> <bt>", args[0]);
-        std::process::exit(1);
-    }
-
-    let fname = &args[1];
-    let s: usize = args[2].parse().expect("s must be an integer");
-    let t: usize = args[3].parse().expect("t must be an integer");
-    let b1: usize = args[4].parse().expect("b1 must be an integer");
-    let b2: usize = args[5].parse().expect("b2 must be an integer");
-
-    let graph = parse_graph(fname);
-
-    let start_time = Instant::now();
-    if let Some((_, v)) = network_diversion(&graph, s, t, (b1, b2)) {
-        let secs = start_time.elapsed().as_secs_f64();
-        println!("{:.3} s", secs);
-        for i in 0..v.len() {
-            println!("{},{}", v[i].from(), v[i].to());
+    match args.get(1).map(String::as_str) {
+        Some("solve") => command::solve::run(&args[2..]),
+        Some("generate") => command::generate::run(&args[2..]),
+        Some("verify") => command::verify::run(&args[2..]),
+        Some("odd-path") => command::path::run_odd_path(&args[2..]),
+        Some("even-path") => command::path::run_even_path(&args[2..]),
+        Some("bottleneck-path") => command::path::run_bottleneck_path(&args[2..]),
+        Some("exact-diversion") => command::exact::run(&args[2..]),
+        _ if args.len() == 5 || args.len() == 6 => command::solve::run(&args[1..]),
+        _ => {
+            eprintln!("{}", USAGE.replace("{0}", &args[0]));
+            std::process::exit(1);
         }
-    } else {
-        let secs = start_time.elapsed().as_secs_f64();
-        println!("{:.3} s: WARN: No cut found", secs);
     }
 }
@@ -0,0 +1,90 @@
+use crate::structure::graph::edge::Edge;
+use crate::structure::weight::Weight;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::ops::Index;
+
+/// Like `UndirectedGraph`, but every edge is only stored at its tail, so `N(u)` are the arcs leaving u.
+#[derive(PartialEq, Clone)]
+pub struct DirectedGraph<W, E>
+where
+    W: Weight,
+    E: Edge<W>,
+{
+    adj_list: Vec<Vec<E>>,
+    n: usize,
+    m: usize,
+    _marker: PhantomData<W>,
+}
+
+impl<W: Weight, E: Edge<W>> DirectedGraph<W, E> {
+    pub fn new(n: usize) -> Self {
+        DirectedGraph {
+            adj_list: (0..n).map(|_| Vec::new()).collect(),
+            n,
+            m: 0,
+            _marker: PhantomData,
+        }
+    }
+    pub fn n(&self) -> usize {
+        self.n
+    }
+    pub fn m(&self) -> usize {
+        self.m
+    }
+    pub fn add_arc(&mut self, e: E) {
+        self.adj_list[e.from()].push(e);
+        self.m += 1;
+    }
+    pub fn has_arc(&self, u: usize, v: usize) -> bool {
+        self.adj_list[u].iter().any(|e| e.to() == v)
+    }
+    pub fn find_arc(&self, u: usize, v: usize) -> Option<&E> {
+        self.adj_list[u].iter().find(|e| e.to() == v)
+    }
+    /// The arc from u to v, which has to exist.
+    pub fn arc(&self, u: usize, v: usize) -> E {
+        self.find_arc(u, v)
+            .unwrap_or_else(|| panic!("The arc {} -> {} doesn't exist", u, v))
+            .clone()
+    }
+    pub fn delete_arcs(&mut self, r: &[E]) {
+        for e in r {
+            let before = self.adj_list[e.from()].len();
+            self.adj_list[e.from()].retain(|f| f.to() != e.to());
+            self.m -= before - self.adj_list[e.from()].len();
+        }
+    }
+    #[allow(non_snake_case)]
+    pub fn N(&self, u: usize) -> &Vec<E> {
+        &self.adj_list[u]
+    }
+    pub fn vertices(&self) -> impl Iterator<Item = usize> {
+        0..self.n
+    }
+    pub fn arcs(&self) -> Vec<E> {
+        self.adj_list.iter().flatten().cloned().collect()
+    }
+}
+
+impl<W: Weight, E: Edge<W>> Index<usize> for DirectedGraph<W, E> {
+    type Output = Vec<E>;
+    fn index(&self, u: usize) -> &Self::Output {
+        &self.adj_list[u]
+    }
+}
+
+impl<W, E> Debug for DirectedGraph<W, E>
+where
+    W: Weight,
+    E: Edge<W> + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ret = String::new();
+        ret.push_str(format!("DirectedGraph(n = {}, m = {}):\n", self.n, self.m).as_str());
+        for u in self.vertices().take(15) {
+            ret.push_str(format!("  N+({}) = {:?}\n", u, self[u]).as_str());
+        }
+        write!(f, "{}", ret)
+    }
+}
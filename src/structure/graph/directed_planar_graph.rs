@@ -0,0 +1,109 @@
+use crate::structure::graph::directed_graph::DirectedGraph;
+use crate::structure::graph::edge::Edge;
+use crate::structure::graph::planar_edge::PlanarEdge;
+use crate::structure::graph::planar_graph::PlanarGraph;
+use crate::structure::weight::Weight;
+use std::str::FromStr;
+
+/**
+A planar graph where every edge is one or two arcs, such as one-way and two-way streets.
+
+The faces come from the underlying undirected embedding. Every arc a has a dual arc from right(a) to left(a)
+with the weight of a, which is how a closed curve crosses a when a leaves the region on the left of the curve.
+Crossing a in the other direction is free, unless the reverse arc exists, so one-way arcs also get
+a dual arc from left(a) to right(a) with weight 0.
+Every dual arc remembers the (possibly missing) arc it cuts as (left, right).
+*/
+pub struct DirectedPlanarGraph<W: Weight> {
+    planar: PlanarGraph<W>,
+    real: DirectedGraph<W, PlanarEdge<W>>,
+    dual: DirectedGraph<W, PlanarEdge<W>>,
+}
+
+impl<W: Weight> DirectedPlanarGraph<W> {
+    pub fn planar(&self) -> &PlanarGraph<W> {
+        &self.planar
+    }
+    pub fn real(&self) -> &DirectedGraph<W, PlanarEdge<W>> {
+        &self.real
+    }
+    pub fn dual(&self) -> &DirectedGraph<W, PlanarEdge<W>> {
+        &self.dual
+    }
+    pub fn n(&self) -> usize {
+        self.real.n()
+    }
+    pub fn m(&self) -> usize {
+        self.real.m()
+    }
+    pub fn f(&self) -> usize {
+        self.dual.n()
+    }
+
+    /// The arc of G that is cut when a closed curve follows the dual arc d, if any.
+    pub fn crossed(&self, d: &PlanarEdge<W>) -> Option<&PlanarEdge<W>> {
+        self.real.find_arc(d.left(), d.right())
+    }
+
+    /// Orient the edges of the embedding, where `arcs` are triples (u, v, weight) for the arcs u -> v.
+    pub fn from_arcs(planar: PlanarGraph<W>, arcs: &[(usize, usize, W)]) -> Result<Self, String> {
+        let mut real: DirectedGraph<W, PlanarEdge<W>> = DirectedGraph::new(planar.n());
+        for &(u, v, w) in arcs {
+            let mut a = planar
+                .real()
+                .find_edges(u, v)
+                .into_iter()
+                .next()
+                .ok_or(format!(
+                    "The arc {} -> {} is not an edge of the embedding",
+                    u, v
+                ))?;
+            // Parallel arcs are combined like parallel edges, by adding their weights.
+            a.weight = real.find_arc(u, v).map_or(w, |b| b.weight + w);
+            real.delete_arcs(&[a.clone()]);
+            real.add_arc(a);
+        }
+
+        let mut dual = DirectedGraph::new(planar.f());
+        for a in real.arcs() {
+            dual.add_arc(a.rotate_right().reverse());
+            if !real.has_arc(a.to(), a.from()) {
+                let mut free = a.rotate_right();
+                free.weight = 0.into();
+                dual.add_arc(free);
+            }
+        }
+
+        Ok(DirectedPlanarGraph { planar, real, dual })
+    }
+}
+
+impl<W: Weight> FromStr for DirectedPlanarGraph<W> {
+    type Err = String;
+
+    /// The same format as `PlanarGraph`, where every edge line `u v weight` is the arc u -> v.
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let planar: PlanarGraph<W> = str.parse().map_err(String::from)?;
+        let arcs = str
+            .lines()
+            .map(str::trim)
+            .filter(|&l| !l.is_empty() && !l.starts_with('%'))
+            .skip(1 + planar.n())
+            .map(|l| {
+                let ws: Vec<&str> = l.split_whitespace().collect();
+                match ws.as_slice() {
+                    [u, v, w] => Ok((
+                        u.parse()
+                            .map_err(|_| format!("Could not parse the arc {}", l))?,
+                        v.parse()
+                            .map_err(|_| format!("Could not parse the arc {}", l))?,
+                        w.parse()
+                            .map_err(|_| format!("Could not parse the arc {}", l))?,
+                    )),
+                    _ => Err(format!("Could not parse the arc {}", l)),
+                }
+            })
+            .collect::<Result<Vec<(usize, usize, W)>, String>>()?;
+        Self::from_arcs(planar, &arcs)
+    }
+}
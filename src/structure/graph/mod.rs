@@ -1,6 +1,9 @@
+pub mod directed_graph;
+pub mod directed_planar_graph;
 pub mod edge;
 pub mod planar_edge;
 pub mod planar_graph;
-mod point;
+pub mod point;
 pub mod simple_graph_strategy;
+pub mod spatial_index;
 pub mod undirected_graph;
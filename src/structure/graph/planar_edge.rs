@@ -36,6 +36,16 @@ impl<W: Weight> PlanarEdge<W> {
     pub fn right(&self) -> usize {
         self.right
     }
+    /// The same edge of the embedding with another weight, possibly of another type.
+    pub fn with_weight<V: Weight>(&self, weight: V) -> PlanarEdge<V> {
+        PlanarEdge {
+            from: self.from,
+            to: self.to,
+            left: self.left,
+            right: self.right,
+            weight,
+        }
+    }
     pub fn rotate_right(&self) -> Self {
         PlanarEdge {
             from: self.left,
@@ -2,6 +2,7 @@ use crate::structure::graph::edge::Edge;
 use crate::structure::graph::planar_edge::{intersect, PlanarEdge, PrePlanarEdge};
 use crate::structure::graph::point::{compare_edges_clockwise, Point};
 use crate::structure::graph::simple_graph_strategy::{SimpleGraphStrategy, SumWeights};
+use crate::structure::graph::spatial_index::SpatialIndex;
 use crate::structure::graph::undirected_graph::UndirectedGraph;
 use crate::structure::weight::Weight;
 use crate::utility::misc::{debug, repeat};
@@ -11,6 +12,10 @@ use std::str::FromStr;
 pub struct PlanarGraph<W: Weight> {
     real: UndirectedGraph<W, PlanarEdge<W>>,
     dual: UndirectedGraph<W, PlanarEdge<W>>,
+    points: Vec<Point>,
+    /// The neighbours of every vertex, in clockwise order.
+    rotation: Vec<Vec<usize>>,
+    genus: usize,
 }
 
 impl<W: Weight> PlanarGraph<W> {
@@ -29,6 +34,100 @@ impl<W: Weight> PlanarGraph<W> {
     pub fn f(&self) -> usize {
         self.dual.n()
     }
+    pub fn points(&self) -> &Vec<Point> {
+        &self.points
+    }
+    /// The genus of the surface that the rotation system embeds G in: 0 for the plane, 1 for the torus, and so on.
+    /// It follows from Euler's formula n - m + f = 2 - 2g, for every connected component.
+    pub fn genus(&self) -> usize {
+        self.genus
+    }
+    pub fn is_planar(&self) -> bool {
+        self.genus == 0
+    }
+    /// The same embedding, with the weight of every edge (and its dual edge) replaced by f(weight).
+    pub fn map_weights<V: Weight>(&self, f: impl Fn(W) -> V) -> PlanarGraph<V> {
+        PlanarGraph {
+            real: self.real.map_edges(|e| e.with_weight(f(e.weight))),
+            dual: self.dual.map_edges(|e| e.with_weight(f(e.weight))),
+            points: self.points.clone(),
+            rotation: self.rotation.clone(),
+            genus: self.genus,
+        }
+    }
+    /// The edges around a face, each with the face on its left, in the order in which `determine_faces` walked them.
+    pub fn face_walk(&self, face: usize) -> Vec<PlanarEdge<W>> {
+        let first = match self.real.edges().into_iter().find(|e| e.left == face) {
+            Some(e) => e,
+            None => return Vec::new(),
+        };
+        let mut walk = vec![first.clone()];
+        loop {
+            let curr = walk.last().unwrap();
+            let around = &self.rotation[curr.to()];
+            let id = around
+                .iter()
+                .position(|&v| v == curr.from())
+                .expect("Couldn't find the reverse edge");
+            let next_to = around[(id + 1) % around.len()];
+            // Like `determine_faces`, the walk ends when it gets back to its first edge.
+            if (curr.to(), next_to) == (first.from(), first.to()) {
+                return walk;
+            }
+            let next = self.real[curr.to()]
+                .iter()
+                .find(|e| e.to() == next_to)
+                .expect("Couldn't find the next edge")
+                .clone();
+            walk.push(next);
+        }
+    }
+    /**
+    Builds the embedding of a rotation system, for example of a periodic grid on the torus, where the coordinates
+    can't determine the order of the edges around a vertex.
+    `rotations[u]` lists the neighbours v of u in clockwise order, with the weight of the edge (u, v),
+    and every edge has to be listed at both of its endpoints. The weight is taken from its smaller endpoint.
+    */
+    pub fn from_rotations(
+        points: Vec<Point>,
+        rotations: Vec<Vec<(usize, W)>>,
+    ) -> Result<Self, &'static str> {
+        let n = rotations.len();
+        if points.len() != n {
+            return Err("Every vertex needs both a point and a rotation");
+        }
+        let mut pre = PrePlanarGraph::empty(n, false);
+        for (u, around) in rotations.iter().enumerate() {
+            for (i, &(v, w)) in around.iter().enumerate() {
+                if v >= n || v == u || around[..i].iter().any(|&(x, _)| x == v) {
+                    return Err(
+                        "A rotation has a loop, a repeated neighbour, or an unknown vertex",
+                    );
+                }
+                if !rotations[v].iter().any(|&(x, _)| x == u) {
+                    return Err("Every edge has to be in the rotation of both of its endpoints");
+                }
+                if u < v {
+                    pre.graph.add_edge(PrePlanarEdge::new(u, v, w));
+                }
+            }
+        }
+        for (u, around) in rotations.iter().enumerate() {
+            pre.graph.adj_list[u]
+                .sort_by_key(|e| around.iter().position(|&(x, _)| x == e.to()).unwrap());
+        }
+        pre.embed(points)
+    }
+    pub fn spatial_index(&self) -> SpatialIndex {
+        let segments = self
+            .real
+            .edges()
+            .into_iter()
+            .filter(|e| e.from() < e.to())
+            .map(|e| (e.from(), e.to()))
+            .collect();
+        SpatialIndex::new(self.points.clone(), segments)
+    }
     pub fn parse<S: SimpleGraphStrategy>(
         str: &str,
         assert_planarity: bool,
@@ -132,7 +231,19 @@ impl<W: Weight> PrePlanarGraph<W> {
         }
 
         self.sort_edges(&points);
+        self.embed(points)
+    }
+
+    /// The faces and dual of the rotation system that is given by the order of the adjacency lists.
+    fn embed(mut self, points: Vec<Point>) -> Result<PlanarGraph<W>, &'static str> {
         let f = self.determine_faces()?;
+        let genus = self.genus(f)?;
+        let rotation = self
+            .graph
+            .adj_list
+            .iter()
+            .map(|xs| xs.iter().map(|e| e.to()).collect())
+            .collect();
 
         let mut real = UndirectedGraph::new(self.graph.n());
         let mut dual = UndirectedGraph::new(f);
@@ -145,7 +256,13 @@ impl<W: Weight> PrePlanarGraph<W> {
             })
         });
 
-        Ok(PlanarGraph { real, dual })
+        Ok(PlanarGraph {
+            real,
+            dual,
+            points,
+            rotation,
+            genus,
+        })
     }
 
     fn sort_edges(&mut self, points: &Vec<Point>) {
@@ -159,9 +276,11 @@ impl<W: Weight> PrePlanarGraph<W> {
         let adj_list_copy = adj_list.clone();
         let mut current_face = 0;
         for start_vertex in 0..n {
-            for mut curr_line_id in 0..adj_list[start_vertex].len() {
+            for start_line_id in 0..adj_list[start_vertex].len() {
+                let mut curr_line_id = start_line_id;
                 let mut curr_line = &adj_list_copy[start_vertex][curr_line_id];
                 if adj_list[start_vertex][curr_line_id].left.is_none() {
+                    // A face may pass a vertex more than once, so the walk ends at its first edge, not its first vertex.
                     loop {
                         adj_list[curr_line.from][curr_line_id].left = Some(current_face);
                         let id = adj_list_copy[curr_line.to]
@@ -172,7 +291,7 @@ impl<W: Weight> PrePlanarGraph<W> {
                         curr_line_id = (id + 1) % adj_list[curr_line.to].len();
                         curr_line = &adj_list_copy[curr_line.to][curr_line_id];
 
-                        if curr_line.from == start_vertex {
+                        if curr_line.from == start_vertex && curr_line_id == start_line_id {
                             break;
                         }
                     }
@@ -188,28 +307,57 @@ impl<W: Weight> PrePlanarGraph<W> {
                 }
             }
         }
-        if self.graph.m() > n + current_face || n + current_face - self.graph.m() != 2 {
-            debug(format!(
-                "n = {}, m = {}, f = {}",
-                self.graph.n(),
-                self.graph.m(),
-                current_face
-            ));
+        Ok(current_face)
+    }
+
+    /// The genus of the embedding with f faces, from n - m + f = 2 - 2g for every component that has an edge.
+    fn genus(&self, f: usize) -> Result<usize, &'static str> {
+        let n = self.graph.n();
+        let mut component = repeat(n, None);
+        let mut components = 0;
+        let mut vertices = 0;
+        for start in 0..n {
+            if component[start].is_some() || self.graph.adj_list[start].is_empty() {
+                continue;
+            }
+            component[start] = Some(components);
+            let mut stack = vec![start];
+            while let Some(u) = stack.pop() {
+                vertices += 1;
+                for e in &self.graph.adj_list[u] {
+                    if component[e.to()].is_none() {
+                        component[e.to()] = Some(components);
+                        stack.push(e.to());
+                    }
+                }
+            }
+            components += 1;
+        }
+        let euler = (vertices + f) as i64 - self.graph.m() as i64;
+        let twice_genus = 2 * components as i64 - euler;
+        if twice_genus < 0 || twice_genus % 2 != 0 {
+            return Err("The faces don't match Euler's formula for any surface");
+        }
+        let genus = (twice_genus / 2) as usize;
+        if genus > 0 {
             debug(format!(
-                "We should have had {} - {} + 2 = {} regions, but we found {}.",
-                self.graph.m(),
+                "n = {}, m = {}, f = {}, with {} components",
                 n,
-                self.graph.m() - self.graph.n() + 2,
-                current_face
+                self.graph.m(),
+                f,
+                components
             ));
             debug(format!(
-                "Either we don't have the correct faces, or Euler's formula is wrong :thinkin:"
+                "A plane embedding would have had {} regions, but we found {}, so the rotation system has genus {}.",
+                f + 2 * genus,
+                f,
+                genus
             ));
             if self.assert_planarity {
-                panic!("Incorrect number of regions compared to vertices and edges!");
+                return Err("The rotation system is not planar, it has a higher genus!");
             }
         }
-        Ok(current_face)
+        Ok(genus)
     }
 
     fn assert_planarity(&self, points: &Vec<Point>) -> Result<(), &'static str> {
@@ -268,3 +416,37 @@ impl<W: Weight> FromStr for PlanarGraph<W> {
         Self::parse::<SumWeights>(str, false)
     }
 }
+
+#[cfg(test)]
+mod test_planar_graph {
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::structure::graph::point::Point;
+
+    #[test]
+    fn test_face_passing_a_vertex_twice() {
+        // The only face of a path walks 0, 1, 2, 1 and back to 0, and passes 1 twice.
+        let planar: PlanarGraph<u64> = "3 2\n0 0 0\n1 1 0\n2 2 0\n0 1 1\n1 2 1\n".parse().unwrap();
+        assert_eq!(planar.f(), 1);
+        assert_eq!(planar.genus(), 0);
+        assert_eq!(planar.face_walk(0).len(), 4);
+    }
+
+    #[test]
+    fn test_rotation_system_of_k4() {
+        let points: Vec<Point> = (0..4).map(|i| Point::new(i as f64, 0.0)).collect();
+        let around = |vs: [usize; 3]| vs.map(|v| (v, 1u64)).to_vec();
+        // Reversing the rotation at one vertex of the plane K4 gives two faces on the torus instead of four.
+        let plane = vec![
+            around([1, 3, 2]),
+            around([0, 2, 3]),
+            around([0, 3, 1]),
+            around([0, 1, 2]),
+        ];
+        let mut torus = plane.clone();
+        torus[0].reverse();
+        let plane = PlanarGraph::from_rotations(points.clone(), plane).unwrap();
+        let torus = PlanarGraph::from_rotations(points, torus).unwrap();
+        assert_eq!((plane.f(), plane.genus()), (4, 0));
+        assert_eq!((torus.f(), torus.genus()), (2, 1));
+    }
+}
@@ -1,10 +1,9 @@
 use crate::structure::graph::edge::Edge;
-use crate::structure::graph::planar_edge::PrePlanarEdge;
 use crate::structure::weight::Weight;
 use std::cmp::Ordering::{self, Equal};
 use std::ops::{Add, Sub};
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
@@ -17,6 +16,9 @@ impl Point {
     fn angle(&self) -> f64 {
         self.y.atan2(self.x)
     }
+    pub fn distance(&self, other: &Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
 }
 
 impl Add for Point {
@@ -35,10 +37,10 @@ impl Sub for Point {
     }
 }
 
-pub fn compare_edges_clockwise<'a, W: Weight>(
+pub fn compare_edges_clockwise<'a, W: Weight, E: Edge<W>>(
     center: &'a Point,
     points: &'a Vec<Point>,
-) -> impl FnMut(&PrePlanarEdge<W>, &PrePlanarEdge<W>) -> Ordering + 'a {
+) -> impl FnMut(&E, &E) -> Ordering + 'a {
     |a, b| {
         let fa = (points[a.to()] - *center).angle();
         let fb = (points[b.to()] - *center).angle();
@@ -0,0 +1,176 @@
+use crate::structure::graph::point::Point;
+
+/**
+A uniform grid over the vertices and edges of a straight-line embedding, used to snap coordinates
+to the nearest vertex or the nearest edge. Every edge is stored in all the cells that its bounding box overlaps.
+*/
+pub struct SpatialIndex {
+    points: Vec<Point>,
+    segments: Vec<(usize, usize)>,
+    origin: Point,
+    cell: f64,
+    cols: usize,
+    rows: usize,
+    vertex_cells: Vec<Vec<usize>>,
+    segment_cells: Vec<Vec<usize>>,
+}
+
+impl SpatialIndex {
+    pub fn new(points: Vec<Point>, segments: Vec<(usize, usize)>) -> Self {
+        let mut lo = points.first().copied().unwrap_or(Point::new(0.0, 0.0));
+        let mut hi = lo;
+        for p in &points {
+            lo = Point::new(lo.x.min(p.x), lo.y.min(p.y));
+            hi = Point::new(hi.x.max(p.x), hi.y.max(p.y));
+        }
+        // Roughly one vertex per cell.
+        let area = ((hi.x - lo.x) * (hi.y - lo.y)).max(f64::MIN_POSITIVE);
+        let cell = (area / points.len().max(1) as f64)
+            .sqrt()
+            .max((hi.x - lo.x).max(hi.y - lo.y) / 1024.0)
+            .max(f64::MIN_POSITIVE);
+        let cols = ((hi.x - lo.x) / cell) as usize + 1;
+        let rows = ((hi.y - lo.y) / cell) as usize + 1;
+
+        let mut index = SpatialIndex {
+            points,
+            segments,
+            origin: lo,
+            cell,
+            cols,
+            rows,
+            vertex_cells: vec![Vec::new(); cols * rows],
+            segment_cells: vec![Vec::new(); cols * rows],
+        };
+        for u in 0..index.points.len() {
+            let (i, j) = index.cell_of(&index.points[u]);
+            index.vertex_cells[j * cols + i].push(u);
+        }
+        for k in 0..index.segments.len() {
+            let (u, v) = index.segments[k];
+            let (i1, j1) = index.cell_of(&index.points[u]);
+            let (i2, j2) = index.cell_of(&index.points[v]);
+            for j in j1.min(j2)..=j1.max(j2) {
+                for i in i1.min(i2)..=i1.max(i2) {
+                    index.segment_cells[j * cols + i].push(k);
+                }
+            }
+        }
+        index
+    }
+
+    /// The closest vertex to `p`, together with the distance to it.
+    pub fn nearest_vertex(&self, p: &Point) -> Option<(usize, f64)> {
+        self.nearest(p, &self.vertex_cells, |u| self.points[u].distance(p))
+    }
+
+    /// The closest edge to `p`, together with the distance to it.
+    pub fn nearest_edge(&self, p: &Point) -> Option<((usize, usize), f64)> {
+        self.nearest(p, &self.segment_cells, |k| {
+            let (u, v) = self.segments[k];
+            distance_to_segment(p, &self.points[u], &self.points[v])
+        })
+        .map(|(k, d)| (self.segments[k], d))
+    }
+
+    fn cell_of(&self, p: &Point) -> (usize, usize) {
+        let clamp = |x: f64, len: usize| (x.max(0.0) as usize).min(len - 1);
+        (
+            clamp((p.x - self.origin.x) / self.cell, self.cols),
+            clamp((p.y - self.origin.y) / self.cell, self.rows),
+        )
+    }
+
+    /**
+    Search the rings of cells around the cell of `p`, and stop as soon as the best candidate so far
+    is closer than anything outside of the cells that have been searched.
+    */
+    fn nearest<F: Fn(usize) -> f64>(
+        &self,
+        p: &Point,
+        cells: &[Vec<usize>],
+        dist: F,
+    ) -> Option<(usize, f64)> {
+        let (ci, cj) = self.cell_of(p);
+        let mut best: Option<(usize, f64)> = None;
+        for k in 0..self.cols.max(self.rows) {
+            let (i1, i2) = (ci.saturating_sub(k), (ci + k).min(self.cols - 1));
+            let (j1, j2) = (cj.saturating_sub(k), (cj + k).min(self.rows - 1));
+            for j in j1..=j2 {
+                for i in i1..=i2 {
+                    if i != i1 && i != i2 && j != j1 && j != j2 {
+                        continue;
+                    }
+                    for &x in &cells[j * self.cols + i] {
+                        let d = dist(x);
+                        if best.is_none_or(|(_, b)| d < b) {
+                            best = Some((x, d));
+                        }
+                    }
+                }
+            }
+            let covered = i1 == 0 && j1 == 0 && i2 == self.cols - 1 && j2 == self.rows - 1;
+            // Everything outside the searched square is at least this far away from p.
+            let searched = [
+                p.x - (self.origin.x + i1 as f64 * self.cell),
+                self.origin.x + (i2 + 1) as f64 * self.cell - p.x,
+                p.y - (self.origin.y + j1 as f64 * self.cell),
+                self.origin.y + (j2 + 1) as f64 * self.cell - p.y,
+            ];
+            let bound = searched.into_iter().fold(f64::INFINITY, f64::min);
+            if covered || best.is_some_and(|(_, b)| b <= bound) {
+                break;
+            }
+        }
+        best
+    }
+}
+
+fn distance_to_segment(p: &Point, a: &Point, b: &Point) -> f64 {
+    let ab = *b - *a;
+    let ap = *p - *a;
+    let len = ab.x * ab.x + ab.y * ab.y;
+    if len == 0.0 {
+        return p.distance(a);
+    }
+    let t = ((ap.x * ab.x + ap.y * ab.y) / len).clamp(0.0, 1.0);
+    p.distance(&Point::new(a.x + t * ab.x, a.y + t * ab.y))
+}
+
+#[cfg(test)]
+mod test_spatial_index {
+    use crate::structure::graph::point::Point;
+    use crate::structure::graph::spatial_index::{distance_to_segment, SpatialIndex};
+    use crate::utility::generator::{delaunay, uniform_points};
+    use crate::utility::random::Random;
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        let mut random = Random::new(3);
+        let instance = delaunay(uniform_points(&mut random, 300));
+        let segments: Vec<(usize, usize)> =
+            instance.edges.iter().map(|&(u, v, _)| (u, v)).collect();
+        let index = SpatialIndex::new(instance.points.clone(), segments.clone());
+
+        for _ in 0..200 {
+            let p = Point::new(
+                random.range_f64(-200.0, 1200.0),
+                random.range_f64(-200.0, 1200.0),
+            );
+            let (_, d) = index.nearest_vertex(&p).unwrap();
+            let expected = instance
+                .points
+                .iter()
+                .map(|q| q.distance(&p))
+                .fold(f64::INFINITY, f64::min);
+            assert_eq!(d, expected);
+
+            let (_, d) = index.nearest_edge(&p).unwrap();
+            let expected = segments
+                .iter()
+                .map(|&(u, v)| distance_to_segment(&p, &instance.points[u], &instance.points[v]))
+                .fold(f64::INFINITY, f64::min);
+            assert_eq!(d, expected);
+        }
+    }
+}
@@ -53,6 +53,14 @@ impl<W: Weight, E: Edge<W>> UndirectedGraph<W, E> {
             .filter(|e| e.to() == v)
             .collect()
     }
+    /// The edge from u to v, which has to exist.
+    pub fn edge(&self, u: usize, v: usize) -> E {
+        self.adj_list[u]
+            .iter()
+            .find(|e| e.to() == v)
+            .unwrap_or_else(|| panic!("The edge ({}, {}) doesn't exist", u, v))
+            .clone()
+    }
     pub fn delete_edges(&mut self, r: &Vec<E>) {
         for e in r {
             self.adj_list[e.from()].retain(|f| f.to() != e.to());
@@ -73,6 +81,19 @@ impl<W: Weight, E: Edge<W>> UndirectedGraph<W, E> {
     pub fn edges(&self) -> Vec<E> {
         self.adj_list.clone().into_iter().flatten().collect()
     }
+    /// The same graph, with every edge replaced by f(edge), in the same order.
+    pub fn map_edges<V: Weight, F: Edge<V>>(&self, f: impl Fn(&E) -> F) -> UndirectedGraph<V, F> {
+        UndirectedGraph {
+            adj_list: self
+                .adj_list
+                .iter()
+                .map(|es| es.iter().map(&f).collect())
+                .collect(),
+            n: self.n,
+            m: self.m,
+            _marker: PhantomData,
+        }
+    }
 }
 
 impl<W: Weight, E: Edge<W>> From<String> for UndirectedGraph<W, E> {
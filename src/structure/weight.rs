@@ -1,6 +1,6 @@
 use std::cmp::Ordering;
 use std::cmp::Ordering::Equal;
-use std::fmt::{Debug, Display};
+use std::fmt::{Debug, Display, Formatter};
 use std::ops::{Add, Div, Sub};
 use std::str::FromStr;
 
@@ -49,3 +49,47 @@ impl<T: PartialOrd + PartialEq> Eq for Order<T> {}
 pub trait Weighted<W: Weight> {
     fn weight(&self) -> W;
 }
+
+/// A weight that is compared by its cost first, and by the tie-breaker second.
+/// It is written as `cost,tie`, where the tie-breaker may be left out.
+#[derive(PartialEq, PartialOrd, Clone, Copy, Default, Debug)]
+pub struct Lexicographic<W: Weight, T: Weight = f64>(pub W, pub T);
+
+impl<W: Weight, T: Weight> Add for Lexicographic<W, T> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Lexicographic(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+impl<W: Weight, T: Weight> Sub for Lexicographic<W, T> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Lexicographic(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+impl<W: Weight, T: Weight> Div for Lexicographic<W, T> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        Lexicographic(self.0 / rhs.0, self.1 / rhs.1)
+    }
+}
+impl<W: Weight, T: Weight> From<u32> for Lexicographic<W, T> {
+    fn from(value: u32) -> Self {
+        Lexicographic(value.into(), 0.into())
+    }
+}
+impl<W: Weight, T: Weight> FromStr for Lexicographic<W, T> {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (cost, tie) = s.split_once(',').unwrap_or((s, "0"));
+        Ok(Lexicographic(
+            cost.trim().parse().map_err(|e| format!("{}", e))?,
+            tie.trim().parse().map_err(|e| format!("{}", e))?,
+        ))
+    }
+}
+impl<W: Weight, T: Weight> Display for Lexicographic<W, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.0, self.1)
+    }
+}
@@ -0,0 +1,338 @@
+use crate::structure::graph::point::Point;
+use crate::utility::random::Random;
+use std::collections::BTreeSet;
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+
+/**
+A generated instance with a straight-line embedding, printed in the same `.in` format that
+`PlanarGraph::parse` reads: first `n m`, then `n` lines `v_id x y`, then `m` lines `u v weight`.
+*/
+pub struct Instance {
+    pub points: Vec<Point>,
+    pub edges: Vec<(usize, usize, f64)>,
+}
+
+impl Instance {
+    fn new(points: Vec<Point>, edges: BTreeSet<(usize, usize)>) -> Self {
+        Instance {
+            points,
+            edges: edges.into_iter().map(|(u, v)| (u, v, 1.0)).collect(),
+        }
+    }
+    pub fn n(&self) -> usize {
+        self.points.len()
+    }
+    pub fn m(&self) -> usize {
+        self.edges.len()
+    }
+    /// Replace all weights by integers drawn uniformly from [lo, hi].
+    pub fn randomize_weights(&mut self, random: &mut Random, lo: u32, hi: u32) {
+        for (_, _, w) in self.edges.iter_mut() {
+            // In u64, since the range of [0, u32::MAX] doesn't fit in a u32.
+            *w = (lo as u64 + random.below((hi as u64 - lo as u64 + 1) as usize) as u64) as f64;
+        }
+    }
+}
+
+impl Display for Instance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {}", self.n(), self.m())?;
+        for (i, p) in self.points.iter().enumerate() {
+            writeln!(f, "{} {} {}", i, p.x, p.y)?;
+        }
+        for (u, v, w) in &self.edges {
+            writeln!(f, "{} {} {}", u, v, w)?;
+        }
+        Ok(())
+    }
+}
+
+fn ordered(u: usize, v: usize) -> (usize, usize) {
+    if u < v {
+        (u, v)
+    } else {
+        (v, u)
+    }
+}
+
+/// Rounded to a few decimals, so that the printed instances stay readable.
+fn on_circle(radius: f64, angle: f64) -> Point {
+    let round = |x: f64| (x * 1e6).round() / 1e6;
+    Point::new(round(radius * angle.cos()), round(radius * angle.sin()))
+}
+
+/// A `width` x `height` grid, vertex `(i, j)` gets the id `j * width + i`.
+pub fn grid(width: usize, height: usize) -> Instance {
+    let mut points = Vec::new();
+    let mut edges = BTreeSet::new();
+    for j in 0..height {
+        for i in 0..width {
+            let u = j * width + i;
+            points.push(Point::new(i as f64, j as f64));
+            if i + 1 < width {
+                edges.insert((u, u + 1));
+            }
+            if j + 1 < height {
+                edges.insert((u, u + width));
+            }
+        }
+    }
+    Instance::new(points, edges)
+}
+
+/**
+A `width` x `height` grid whose rows and columns wrap around, with the vertex ids of `grid`. Every vertex gets the same
+rotation (right, down, left, up), which embeds it on the torus, so it is no straight-line `Instance`, but the points
+and rotations for `PlanarGraph::from_rotations`, with unit weights. Both sides have to be at least 3.
+*/
+pub fn torus(width: usize, height: usize) -> (Vec<Point>, Vec<Vec<(usize, f64)>>) {
+    let mut points = Vec::new();
+    let mut rotations = Vec::new();
+    for j in 0..height {
+        for i in 0..width {
+            let id = |i: usize, j: usize| (j % height) * width + i % width;
+            points.push(Point::new(i as f64, j as f64));
+            rotations.push(vec![
+                (id(i + 1, j), 1.0),
+                (id(i, j + height - 1), 1.0),
+                (id(i + width - 1, j), 1.0),
+                (id(i, j + 1), 1.0),
+            ]);
+        }
+    }
+    (points, rotations)
+}
+
+/// A hub with id `0`, connected to a cycle of `spokes >= 3` vertices around it.
+pub fn wheel(spokes: usize) -> Result<Instance, &'static str> {
+    if spokes < 3 {
+        return Err("A wheel needs at least 3 spokes");
+    }
+    let mut points = vec![Point::new(0.0, 0.0)];
+    let mut edges = BTreeSet::new();
+    for i in 0..spokes {
+        let a = 2.0 * PI * i as f64 / spokes as f64;
+        points.push(on_circle(1.0, a));
+        edges.insert((0, i + 1));
+        edges.insert(ordered(i + 1, (i + 1) % spokes + 1));
+    }
+    Ok(Instance::new(points, edges))
+}
+
+/**
+`rings >= 1` concentric cycles of `spokes >= 3` vertices each, where consecutive rings are connected along the spokes.
+Vertex `j` of ring `i` gets the id `i * spokes + j`.
+*/
+pub fn cylinder(rings: usize, spokes: usize) -> Result<Instance, &'static str> {
+    if rings < 1 || spokes < 3 {
+        return Err("A cylinder needs at least 1 ring of at least 3 spokes");
+    }
+    let mut points = Vec::new();
+    let mut edges = BTreeSet::new();
+    for i in 0..rings {
+        for j in 0..spokes {
+            let a = 2.0 * PI * j as f64 / spokes as f64;
+            let u = i * spokes + j;
+            points.push(on_circle((i + 1) as f64, a));
+            edges.insert(ordered(u, i * spokes + (j + 1) % spokes));
+            if i + 1 < rings {
+                edges.insert((u, u + spokes));
+            }
+        }
+    }
+    Ok(Instance::new(points, edges))
+}
+
+/// `n` points drawn uniformly from the square [0, 1000) x [0, 1000).
+pub fn uniform_points(random: &mut Random, n: usize) -> Vec<Point> {
+    (0..n)
+        .map(|_| Point::new(random.range_f64(0.0, 1000.0), random.range_f64(0.0, 1000.0)))
+        .collect()
+}
+
+/// `n` points drawn from `clusters` gaussian clusters with uniformly placed centers.
+pub fn clustered_points(random: &mut Random, n: usize, clusters: usize) -> Vec<Point> {
+    let centers = uniform_points(random, clusters.max(1));
+    let spread = 1000.0 / (4.0 * (centers.len() as f64).sqrt());
+    (0..n)
+        .map(|_| {
+            let c = centers[random.below(centers.len())];
+            Point::new(
+                c.x + spread * random.gaussian(),
+                c.y + spread * random.gaussian(),
+            )
+        })
+        .collect()
+}
+
+/**
+The Delaunay triangulation of the given points, computed with the Bowyer-Watson algorithm.
+Duplicate points are kept as isolated vertices, so they should be avoided.
+*/
+pub fn delaunay(points: Vec<Point>) -> Instance {
+    let n = points.len();
+    let (mut lo, mut hi) = (Point::new(0.0, 0.0), Point::new(1.0, 1.0));
+    if let Some(p) = points.first() {
+        lo = *p;
+        hi = *p;
+    }
+    for p in &points {
+        lo = Point::new(lo.x.min(p.x), lo.y.min(p.y));
+        hi = Point::new(hi.x.max(p.x), hi.y.max(p.y));
+    }
+    let span = (hi.x - lo.x).max(hi.y - lo.y).max(1.0) * 1e4;
+    let mid = Point::new((lo.x + hi.x) / 2.0, (lo.y + hi.y) / 2.0);
+
+    // Bowyer-Watson starts from a triangle that contains all the points.
+    let mut all = points.clone();
+    all.push(Point::new(mid.x - span, mid.y - span));
+    all.push(Point::new(mid.x + span, mid.y - span));
+    all.push(Point::new(mid.x, mid.y + span));
+    let mut triangles: Vec<[usize; 3]> = vec![[n, n + 1, n + 2]];
+
+    for p in 0..n {
+        let (bad, good): (Vec<[usize; 3]>, Vec<[usize; 3]>) = triangles
+            .into_iter()
+            .partition(|tri| in_circumcircle(&all, tri, &all[p]));
+        let mut boundary: Vec<(usize, usize)> = Vec::new();
+        for tri in &bad {
+            for k in 0..3 {
+                let e = ordered(tri[k], tri[(k + 1) % 3]);
+                if let Some(i) = boundary.iter().position(|&f| f == e) {
+                    boundary.swap_remove(i);
+                } else {
+                    boundary.push(e);
+                }
+            }
+        }
+        triangles = good;
+        for (u, v) in boundary {
+            triangles.push(counterclockwise(&all, [u, v, p]));
+        }
+    }
+
+    let mut edges = BTreeSet::new();
+    for tri in triangles {
+        for k in 0..3 {
+            let (u, v) = (tri[k], tri[(k + 1) % 3]);
+            if u < n && v < n {
+                edges.insert(ordered(u, v));
+            }
+        }
+    }
+    Instance::new(points, edges)
+}
+
+fn cross(o: &Point, a: &Point, b: &Point) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+fn counterclockwise(points: &[Point], [a, b, c]: [usize; 3]) -> [usize; 3] {
+    if cross(&points[a], &points[b], &points[c]) < 0.0 {
+        [a, c, b]
+    } else {
+        [a, b, c]
+    }
+}
+
+/// Assumes that the triangle is oriented counterclockwise.
+fn in_circumcircle(points: &[Point], tri: &[usize; 3], d: &Point) -> bool {
+    let [a, b, c] = tri.map(|i| points[i] - *d);
+    let det = (a.x * a.x + a.y * a.y) * (b.x * c.y - c.x * b.y)
+        - (b.x * b.x + b.y * b.y) * (a.x * c.y - c.x * a.y)
+        + (c.x * c.x + c.y * c.y) * (a.x * b.y - b.x * a.y);
+    det > 0.0
+}
+
+/**
+A random maximal planar graph on `n >= 3` vertices, built by repeatedly placing a new vertex inside
+a random triangular face and connecting it to the three corners of that face.
+*/
+pub fn random_maximal_planar(random: &mut Random, n: usize) -> Result<Instance, &'static str> {
+    if n < 3 {
+        return Err("A maximal planar graph needs at least 3 vertices");
+    }
+    let mut points = vec![
+        Point::new(0.0, 0.0),
+        Point::new(1000.0, 0.0),
+        Point::new(500.0, 866.0),
+    ];
+    let mut edges = BTreeSet::from([(0, 1), (0, 2), (1, 2)]);
+    let mut faces = vec![[0, 1, 2]];
+    for u in 3..n {
+        let i = random.below(faces.len());
+        let [a, b, c] = faces.swap_remove(i);
+        // Keep away from the sides of the face, so the coordinates stay well separated.
+        let ws = [0, 1, 2].map(|_| random.range_f64(1.0, 2.0));
+        let total = ws[0] + ws[1] + ws[2];
+        let (pa, pb, pc) = (points[a], points[b], points[c]);
+        points.push(Point::new(
+            (ws[0] * pa.x + ws[1] * pb.x + ws[2] * pc.x) / total,
+            (ws[0] * pa.y + ws[1] * pb.y + ws[2] * pc.y) / total,
+        ));
+        for v in [a, b, c] {
+            edges.insert((v, u));
+        }
+        faces.extend([[a, b, u], [b, c, u], [c, a, u]]);
+    }
+    Ok(Instance::new(points, edges))
+}
+
+#[cfg(test)]
+mod test_generator {
+    use crate::structure::graph::planar_graph::PlanarGraph;
+    use crate::structure::graph::simple_graph_strategy::SumWeights;
+    use crate::utility::generator::*;
+    use crate::utility::random::Random;
+
+    fn assert_planar(instance: Instance) {
+        let planar = PlanarGraph::<f64>::parse::<SumWeights>(&instance.to_string(), true)
+            .expect("The generated instance should be a straight-line embedding");
+        assert_eq!(planar.n() + planar.f(), planar.m() + 2);
+    }
+
+    #[test]
+    fn test_generated_instances_are_planar() {
+        let mut random = Random::new(42);
+        assert_planar(grid(7, 5));
+        assert_planar(wheel(9).unwrap());
+        assert_planar(cylinder(4, 6).unwrap());
+        assert_planar(delaunay(uniform_points(&mut random, 200)));
+        assert_planar(delaunay(clustered_points(&mut random, 200, 4)));
+        assert_planar(random_maximal_planar(&mut random, 100).unwrap());
+    }
+
+    #[test]
+    fn test_torus_has_genus_one() {
+        let (points, rotations) = torus(5, 4);
+        let torus = PlanarGraph::from_rotations(points, rotations).unwrap();
+        assert_eq!((torus.n(), torus.m(), torus.f()), (20, 40, 20));
+        assert_eq!(torus.genus(), 1);
+        assert!((0..torus.f()).all(|face| torus.face_walk(face).len() == 4));
+    }
+
+    #[test]
+    fn test_same_seed_same_instance() {
+        let a = delaunay(uniform_points(&mut Random::new(7), 50)).to_string();
+        let b = delaunay(uniform_points(&mut Random::new(7), 50)).to_string();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_maximal_planar_edge_count() {
+        let instance = random_maximal_planar(&mut Random::new(1), 30).unwrap();
+        assert_eq!(instance.m(), 3 * 30 - 6);
+    }
+
+    #[test]
+    fn test_degenerate_arguments() {
+        assert!(wheel(2).is_err());
+        assert!(cylinder(3, 1).is_err());
+        assert!(random_maximal_planar(&mut Random::new(1), 2).is_err());
+
+        let mut instance = grid(3, 2);
+        instance.randomize_weights(&mut Random::new(1), 0, u32::MAX);
+        assert!(instance.edges.iter().all(|&(_, _, w)| w <= u32::MAX as f64));
+    }
+}
@@ -8,6 +8,6 @@ where
 const DEBUG_MODE: bool = false;
 pub fn debug(s: String) {
     if DEBUG_MODE {
-        println!("{}", s);
+        eprintln!("{}", s);
     }
 }
@@ -1 +1,3 @@
+pub mod generator;
 pub mod misc;
+pub mod random;